@@ -55,6 +55,7 @@ fn save_results(result: &str, input_files: &[String]) -> std::io::Result<String>
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     use distributed_mapreduce::client;
     use distributed_mapreduce::functions::FunctionRegistry;
+    use distributed_mapreduce::server::{self, MapReduceService};
 
     println!("\n{}", "Starting Map-Reduce Job...".blue().bold());
 
@@ -64,11 +65,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     registry.register_map_function("char_freq".to_string(), Box::new(CharFrequencyMapper));
     registry.register_reduce_function("max".to_string(), Box::new(MaxReducer));
 
+    // A server started with `cargo run -- server` only knows the built-in
+    // `word_count`/`sum` functions, since `char_freq`/`max` are defined in
+    // this example binary. Run a server with this registry wired in so
+    // `char_freq`/`max` actually resolve server-side.
+    let addr = "127.0.0.1:50061";
+    tokio::spawn(server::run_server_with_service(addr, MapReduceService::new_with_registry(registry)));
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
     // Example usage
     let txt_files = vec!["example.txt".to_string()];
 
     let result = client::run_map_reduce_job(
-        "http://localhost:50051".to_string(),
+        format!("http://{addr}"),
         txt_files.clone(),
         "char_freq".to_string(),
         "max".to_string(),
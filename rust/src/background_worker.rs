@@ -0,0 +1,117 @@
+//! Generic background-task abstraction: a worker implements
+//! [`BackgroundWorker`] and a manager drives it in its own task, recording
+//! its state, last error, and completed-iteration count so it can be
+//! introspected instead of running invisibly until it either finishes or is
+//! silently forgotten.
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// How long to back off before retrying `work()` after it returns `Err`,
+/// mirroring how an `Idle` result makes the driving task wait rather than
+/// spin, so a worker that keeps failing doesn't peg a tokio thread in a
+/// tight loop.
+const ERROR_BACKOFF: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Where a background worker currently stands in its lifecycle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerState {
+    /// Actively running an iteration of `work()`.
+    Busy,
+    /// Between iterations; the manager won't poll again until `wait_until`.
+    Idle { wait_until: SystemTime },
+    /// Finished for good and will not be driven again.
+    Done,
+}
+
+#[async_trait::async_trait]
+pub trait BackgroundWorker: Send {
+    /// Runs one iteration of work and reports what the manager should do
+    /// next: run again immediately (`Busy`), wait until a time (`Idle`), or
+    /// stop driving this worker for good (`Done`).
+    async fn work(&mut self) -> Result<WorkerState, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Snapshot of a background worker's health, updated after every iteration.
+#[derive(Debug, Clone)]
+pub struct WorkerStatus {
+    pub state: WorkerState,
+    pub last_error: Option<String>,
+    pub completed_iterations: u64,
+}
+
+impl Default for WorkerStatus {
+    fn default() -> Self {
+        Self {
+            state: WorkerState::Idle { wait_until: SystemTime::now() },
+            last_error: None,
+            completed_iterations: 0,
+        }
+    }
+}
+
+/// Handle to a background worker driven in its own task; `status()` can be
+/// polled at any time to introspect it without interrupting the task.
+#[derive(Clone)]
+pub struct WorkerHandle {
+    status: Arc<Mutex<WorkerStatus>>,
+}
+
+impl WorkerHandle {
+    pub fn status(&self) -> WorkerStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Spawns `worker`, repeatedly calling `work()` until it reports `Done`,
+/// sleeping until `wait_until` after an `Idle` result, and recording any
+/// error without killing the driving task.
+pub fn spawn_background_worker<W: BackgroundWorker + 'static>(mut worker: W) -> WorkerHandle {
+    let status = Arc::new(Mutex::new(WorkerStatus::default()));
+    let status_for_task = Arc::clone(&status);
+
+    tokio::spawn(async move {
+        loop {
+            status_for_task.lock().unwrap().state = WorkerState::Busy;
+
+            match worker.work().await {
+                Ok(WorkerState::Done) => {
+                    let mut s = status_for_task.lock().unwrap();
+                    s.state = WorkerState::Done;
+                    s.completed_iterations += 1;
+                    s.last_error = None;
+                    break;
+                }
+                Ok(state @ WorkerState::Idle { .. }) => {
+                    let wait_until = match &state {
+                        WorkerState::Idle { wait_until } => *wait_until,
+                        _ => unreachable!(),
+                    };
+                    {
+                        let mut s = status_for_task.lock().unwrap();
+                        s.state = state;
+                        s.completed_iterations += 1;
+                        s.last_error = None;
+                    }
+                    if let Ok(delay) = wait_until.duration_since(SystemTime::now()) {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                Ok(WorkerState::Busy) => {
+                    // Worker wants another iteration immediately.
+                    let mut s = status_for_task.lock().unwrap();
+                    s.completed_iterations += 1;
+                    s.last_error = None;
+                }
+                Err(e) => {
+                    {
+                        let mut s = status_for_task.lock().unwrap();
+                        s.last_error = Some(e.to_string());
+                    }
+                    tokio::time::sleep(ERROR_BACKOFF).await;
+                }
+            }
+        }
+    });
+
+    WorkerHandle { status }
+}
@@ -0,0 +1,153 @@
+//! Content-defined chunking so a single large file can be mapped in parallel
+//! by several workers instead of line-by-line on whichever worker happened
+//! to be handed the whole path.
+use tokio::io::{AsyncReadExt, BufReader};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Result<T> = std::result::Result<T, Error>;
+
+const WINDOW: usize = 64;
+
+/// `(file_path, byte_offset, length)` describing one worker's slice of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkDescriptor {
+    pub file_path: String,
+    pub offset: u64,
+    pub length: u64,
+}
+
+const fn build_gear_table() -> [u64; 256] {
+    // A fixed pseudo-random table, generated once at compile time with
+    // splitmix64 so every build sees the same boundaries for the same bytes.
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E3779B97F4A7C15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        table[i] = z;
+        i += 1;
+    }
+    table
+}
+
+static GEAR: [u64; 256] = build_gear_table();
+
+/// Scans `data` for gear-hash boundaries, snapping each one forward to the
+/// next newline so a chunk never splits a line, and returns the resulting
+/// chunk lengths.
+fn find_chunk_lengths(data: &[u8], min_chunk: usize, max_chunk: usize) -> Vec<usize> {
+    let target_avg = ((min_chunk + max_chunk) / 2).max(WINDOW).next_power_of_two();
+    let mask = (target_avg as u64) - 1;
+
+    let mut lengths = Vec::new();
+    let mut chunk_start = 0usize;
+    let mut hash: u64 = 0;
+    let mut i = 0usize;
+
+    while i < data.len() {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        let len = i - chunk_start + 1;
+
+        if len >= min_chunk && (hash & mask == 0 || len >= max_chunk) {
+            // Snap the boundary forward to (and including) the next newline
+            // so no line is split across two chunks.
+            let mut boundary = i;
+            while boundary < data.len() && data[boundary] != b'\n' {
+                boundary += 1;
+            }
+            boundary = (boundary + 1).min(data.len());
+
+            lengths.push(boundary - chunk_start);
+            chunk_start = boundary;
+            i = boundary;
+            hash = 0;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    if chunk_start < data.len() {
+        lengths.push(data.len() - chunk_start);
+    }
+
+    lengths
+}
+
+/// Splits `path` into content-defined chunks of at least `min_chunk` bytes
+/// (except possibly the last), with a boundary forced once a chunk reaches
+/// `max_chunk` bytes; every boundary is then moved forward to the end of its
+/// line, so a chunk can run past `max_chunk` by up to one line. Because the
+/// boundaries depend only on file content, re-chunking after a small edit
+/// reuses most of the previous chunk offsets.
+pub async fn chunk_file(path: &str, min_chunk: u64, max_chunk: u64) -> Result<Vec<ChunkDescriptor>> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut reader = BufReader::new(file);
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await?;
+
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let lengths = find_chunk_lengths(&data, min_chunk as usize, max_chunk as usize);
+
+    let mut descriptors = Vec::with_capacity(lengths.len());
+    let mut offset = 0u64;
+    for length in lengths {
+        descriptors.push(ChunkDescriptor {
+            file_path: path.to_string(),
+            offset,
+            length: length as u64,
+        });
+        offset += length as u64;
+    }
+
+    Ok(descriptors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn chunks_cover_the_whole_file_without_splitting_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input.txt");
+        let mut contents = String::new();
+        for i in 0..5000 {
+            contents.push_str(&format!("line {i} with some filler text to vary lengths\n"));
+        }
+        tokio::fs::write(&path, &contents).await.unwrap();
+
+        let descriptors = chunk_file(path.to_str().unwrap(), 4096, 16384).await.unwrap();
+        assert!(!descriptors.is_empty());
+
+        // A boundary is moved forward to the end of its line, so a chunk can
+        // run past `max_chunk` by at most one line.
+        let longest_line = contents.lines().map(|l| l.len() as u64 + 1).max().unwrap();
+
+        let mut total = 0u64;
+        for (i, chunk) in descriptors.iter().enumerate() {
+            assert_eq!(chunk.offset, total);
+            total += chunk.length;
+            if i + 1 < descriptors.len() {
+                assert!(chunk.length <= 16384 + longest_line);
+            }
+        }
+        assert_eq!(total, contents.len() as u64);
+
+        // Every chunk boundary lands right after a newline (or at EOF).
+        let mut running = 0u64;
+        for chunk in &descriptors {
+            running += chunk.length;
+            if running < contents.len() as u64 {
+                assert_eq!(contents.as_bytes()[running as usize - 1], b'\n');
+            }
+        }
+    }
+}
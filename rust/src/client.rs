@@ -1,15 +1,30 @@
+use futures_util::future::try_join_all;
+use futures_util::StreamExt;
 use tonic::Request;
 use uuid::Uuid;
 
+use crate::chunking::{self, ChunkDescriptor};
+use crate::config::{Config, S3Config, TransportBackend};
+use crate::fluentd::{self, FluentdSink};
+use crate::input_source;
+use crate::jetstream_transport::{JetStreamTransport, MapTask, ReduceTask};
 use crate::mapreduce::{
     map_reduce_service_client::MapReduceServiceClient,
+    ListWorkersRequest,
     MapRequest,
     PingRequest,
     ReduceRequest,
+    RegisterWorkerRequest,
+    ScheduleJobRequest,
 };
+use crate::shuffle;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+/// How often a worker started via [`run_worker`] pings the primary to keep
+/// its registered entry from going stale, well inside `HEARTBEAT_TIMEOUT`.
+const WORKER_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub struct MapReduceClient {
     client: MapReduceServiceClient<tonic::transport::Channel>,
     worker_id: String,
@@ -35,50 +50,263 @@ impl MapReduceClient {
 }
 
 pub async fn get_txt_files(dir: &str) -> Result<Vec<String>> {
-    let mut txt_files = Vec::new();
-    let mut entries = tokio::fs::read_dir(dir).await?;
-    
-    while let Some(entry) = entries.next_entry().await? {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) == Some("txt") {
-            if let Some(path_str) = path.to_str() {
-                txt_files.push(path_str.to_string());
-            }
-        }
-    }
-    
+    // `dir` may be a local directory or an `s3://bucket/prefix` to list.
+    let source = input_source::resolve(dir, &S3Config::from_env());
+    let entries = source.list(dir).await?;
+
+    let txt_files = entries
+        .into_iter()
+        .filter(|path| path.ends_with(".txt"))
+        .collect();
+
     Ok(txt_files)
 }
 
+/// Splits a local file into content-defined chunks so it can be mapped in
+/// parallel; falls back to a single whole-file chunk for anything that
+/// isn't a plain local path (e.g. `s3://...`) or that fails to chunk.
+async fn plan_chunks(file_path: &str, config: &Config) -> Vec<ChunkDescriptor> {
+    if file_path.starts_with("s3://") {
+        return vec![ChunkDescriptor { file_path: file_path.to_string(), offset: 0, length: 0 }];
+    }
+
+    match chunking::chunk_file(file_path, config.min_chunk_size, config.max_chunk_size).await {
+        Ok(chunks) if !chunks.is_empty() => chunks,
+        _ => vec![ChunkDescriptor { file_path: file_path.to_string(), offset: 0, length: 0 }],
+    }
+}
+
+/// Resolves the addresses map/reduce calls should round-robin across:
+/// every non-dead worker `server_addr`'s coordinator knows about, with its
+/// own self-registered `"self"` entry translated back to `server_addr`
+/// (the only address a client can actually reach it at). Falls back to just
+/// `server_addr` if listing workers fails or turns up none, so a single
+/// unreachable worker or a primary with no remote workers registered still
+/// dispatches exactly as before.
+async fn resolve_worker_addresses(server_addr: &str) -> Vec<String> {
+    let workers = async {
+        let mut client = MapReduceServiceClient::connect(server_addr.to_string()).await?;
+        let response = client.list_workers(Request::new(ListWorkersRequest {})).await?;
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(response.into_inner().workers)
+    }
+    .await;
+
+    let addresses: Vec<String> = match workers {
+        Ok(workers) => workers
+            .into_iter()
+            .filter(|w| w.status != "dead")
+            .map(|w| if w.address == "self" { server_addr.to_string() } else { w.address })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    if addresses.is_empty() {
+        vec![server_addr.to_string()]
+    } else {
+        addresses
+    }
+}
+
 pub async fn run_map_reduce_job(
     server_addr: String,
     files: Vec<String>,
     map_function: String,
     reduce_function: String,
 ) -> Result<String> {
-    let mut client = MapReduceServiceClient::connect(server_addr).await?;
+    let config = Config::new();
 
-    let mut all_intermediate_results = Vec::new();
+    // `MR_TRANSPORT=jetstream` dispatches map/reduce tasks through the
+    // durable NATS work queue instead of calling `server_addr` directly.
+    if config.transport == TransportBackend::Jetstream {
+        return run_map_reduce_job_jetstream(config, files, map_function, reduce_function).await;
+    }
 
-    // Map phase
-    for file_path in files {
-        let request = Request::new(MapRequest {
-            file_path,
-            map_function: map_function.clone(),
-        });
+    run_map_reduce_job_grpc(config, server_addr, files, map_function, reduce_function).await
+}
 
-        let response = client.map(request).await?;
-        all_intermediate_results.extend(response.into_inner().intermediate_results);
+async fn run_map_reduce_job_grpc(
+    config: Config,
+    server_addr: String,
+    files: Vec<String>,
+    map_function: String,
+    reduce_function: String,
+) -> Result<String> {
+    let fluentd = FluentdSink::from_config(&config);
+    let job_id = Uuid::new_v4().to_string();
+    let job_started = std::time::Instant::now();
+
+    fluentd
+        .emit(
+            "job.start",
+            fluentd::fields(&[("job_id", &job_id), ("file_path", &files.join(","))]),
+        )
+        .await;
+
+    let mut chunks = Vec::new();
+    for file_path in &files {
+        chunks.extend(plan_chunks(file_path, &config).await);
     }
 
-    // Reduce phase
-    let request = Request::new(ReduceRequest {
-        intermediate_results: all_intermediate_results,
-        reduce_function,
+    // Round-robin map/reduce calls across every worker the coordinator at
+    // `server_addr` knows about, instead of sending every call to
+    // `server_addr` itself, so a registered worker pool actually shares the
+    // load the hash-partitioned shuffle below was built to spread out.
+    let worker_addrs = resolve_worker_addresses(&server_addr).await;
+
+    // Map phase: every chunk is mapped independently and in parallel, so a
+    // single large file is spread across many map calls instead of one.
+    let map_calls = chunks.into_iter().enumerate().map(|(i, chunk)| {
+        let worker_addr = worker_addrs[i % worker_addrs.len()].clone();
+        let map_function = map_function.clone();
+        let job_id = job_id.clone();
+        async move {
+            let mut client = MapReduceServiceClient::connect(worker_addr).await?;
+            let request = Request::new(MapRequest {
+                file_path: chunk.file_path,
+                map_function,
+                offset: chunk.offset,
+                length: chunk.length,
+                job_id,
+            });
+            let response = client.map(request).await?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(response.into_inner().intermediate_results)
+        }
     });
 
-    let response = client.reduce(request).await?;
-    Ok(response.into_inner().final_result)
+    let all_intermediate_results: Vec<_> = try_join_all(map_calls).await?.into_iter().flatten().collect();
+
+    // Shuffle: partition intermediate pairs by key so every occurrence of a
+    // key lands in the same reduce call, then fan the partitions out in
+    // parallel instead of reducing everything in one request.
+    let num_reducers = config.num_reducers.max(1);
+    let partitions = shuffle::partition_pairs(all_intermediate_results, num_reducers);
+
+    let reduce_calls = partitions.into_iter().filter(|p| !p.is_empty()).enumerate().map(|(i, partition)| {
+        let worker_addr = worker_addrs[i % worker_addrs.len()].clone();
+        let reduce_function = reduce_function.clone();
+        let job_id = job_id.clone();
+        async move {
+            let mut client = MapReduceServiceClient::connect(worker_addr).await?;
+            let request = Request::new(ReduceRequest {
+                intermediate_results: partition,
+                reduce_function,
+                num_reducers: num_reducers as u32,
+                job_id,
+            });
+            let response = client.reduce(request).await?;
+            Ok::<String, Box<dyn std::error::Error + Send + Sync>>(response.into_inner().final_result)
+        }
+    });
+
+    let partition_results = try_join_all(reduce_calls).await?;
+    let result = partition_results.into_iter().filter(|r| !r.is_empty()).collect::<Vec<_>>().join(", ");
+
+    fluentd
+        .emit(
+            "job.complete",
+            fluentd::fields(&[
+                ("job_id", &job_id),
+                ("elapsed_ms", &job_started.elapsed().as_millis().to_string()),
+            ]),
+        )
+        .await;
+
+    Ok(result)
+}
+
+/// Mirror of [`run_map_reduce_job_grpc`] that dispatches map/reduce tasks
+/// through the NATS JetStream work queue instead of calling a single server
+/// directly, so partitions actually spread across whichever workers are
+/// pulling from the queue.
+async fn run_map_reduce_job_jetstream(
+    config: Config,
+    files: Vec<String>,
+    map_function: String,
+    reduce_function: String,
+) -> Result<String> {
+    let fluentd = FluentdSink::from_config(&config);
+    let job_id = Uuid::new_v4().to_string();
+    let job_started = std::time::Instant::now();
+
+    fluentd
+        .emit(
+            "job.start",
+            fluentd::fields(&[("job_id", &job_id), ("file_path", &files.join(","))]),
+        )
+        .await;
+
+    let transport = JetStreamTransport::connect(&config.nats_url).await?;
+
+    let mut chunks = Vec::new();
+    for file_path in &files {
+        chunks.extend(plan_chunks(file_path, &config).await);
+    }
+
+    let num_reducers = config.num_reducers.max(1);
+
+    // Subscribe before publishing, so a map task that finishes between the
+    // publish call and the subscribe call can't have its completion signal
+    // missed entirely.
+    let mut map_done = transport.subscribe_map_done(&job_id).await?;
+
+    let task_ids: Vec<String> = chunks.iter().map(|_| Uuid::new_v4().to_string()).collect();
+    for (chunk, task_id) in chunks.into_iter().zip(&task_ids) {
+        transport
+            .publish_map_task(&MapTask {
+                job_id: job_id.clone(),
+                task_id: task_id.clone(),
+                file_path: chunk.file_path,
+                offset: chunk.offset,
+                length: chunk.length,
+                map_function: map_function.clone(),
+                num_reducers,
+            })
+            .await?;
+    }
+
+    for _ in 0..task_ids.len() {
+        map_done.next().await.ok_or("map-done subscription closed before every map task reported in")?;
+    }
+
+    // Every map task may have staged a partition under its own task id, so a
+    // reduce task for partition `p` has to consider one candidate key per
+    // map task and skip whichever weren't actually written.
+    let mut reduce_done = transport.subscribe_reduce_done(&job_id).await?;
+    for partition in 0..num_reducers {
+        let object_keys = task_ids
+            .iter()
+            .map(|task_id| format!("{job_id}/partition-{partition}/{task_id}"))
+            .collect();
+        transport
+            .publish_reduce_task(&ReduceTask {
+                job_id: job_id.clone(),
+                partition,
+                reduce_function: reduce_function.clone(),
+                object_keys,
+            })
+            .await?;
+    }
+
+    let mut results: Vec<Option<String>> = vec![None; num_reducers];
+    for _ in 0..num_reducers {
+        let message = reduce_done.next().await.ok_or("reduce-done subscription closed before every partition reported in")?;
+        let done: crate::jetstream_transport::ReduceDone = serde_json::from_slice(&message.payload)?;
+        results[done.partition] = Some(done.result);
+    }
+
+    let result = results.into_iter().flatten().filter(|r| !r.is_empty()).collect::<Vec<_>>().join(", ");
+
+    fluentd
+        .emit(
+            "job.complete",
+            fluentd::fields(&[
+                ("job_id", &job_id),
+                ("elapsed_ms", &job_started.elapsed().as_millis().to_string()),
+            ]),
+        )
+        .await;
+
+    Ok(result)
 }
 
 pub async fn ping_server(server_address: String) -> Result<()> {
@@ -86,3 +314,70 @@ pub async fn ping_server(server_address: String) -> Result<()> {
     client.send_heartbeat().await?;
     Ok(())
 }
+
+/// Registers `own_address` with `primary_addr`'s coordinator, returning the
+/// worker id it was assigned. Called once on startup by [`run_worker`].
+async fn register_with_primary(primary_addr: &str, own_address: &str) -> Result<String> {
+    let mut client = MapReduceServiceClient::connect(primary_addr.to_string()).await?;
+    let request = Request::new(RegisterWorkerRequest { address: own_address.to_string() });
+    let response = client.register_worker(request).await?;
+    Ok(response.into_inner().worker_id)
+}
+
+/// Runs a worker process: registers `listen_addr` with `primary_addr`, keeps
+/// that registration alive with a periodic `ping`, and serves `map`/`reduce`
+/// RPCs on `listen_addr` the same way the primary server does, so
+/// `run_map_reduce_job`'s round-robin dispatch has a real second worker to
+/// route to instead of `list_workers` only ever reflecting the primary's own
+/// self-registered entry.
+pub async fn run_worker(primary_addr: String, listen_addr: String) -> Result<()> {
+    let worker_id = register_with_primary(&primary_addr, &listen_addr).await?;
+    println!("Registered with {} as worker '{}'", primary_addr, worker_id);
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(WORKER_HEARTBEAT_INTERVAL).await;
+            let connect = MapReduceServiceClient::connect(primary_addr.clone()).await;
+            let mut client = match connect {
+                Ok(client) => client,
+                Err(e) => {
+                    eprintln!("worker heartbeat: failed to connect to {}: {}", primary_addr, e);
+                    continue;
+                }
+            };
+            let mut request = Request::new(PingRequest {});
+            request.metadata_mut().insert("worker-id", worker_id.parse().unwrap());
+            if let Err(e) = client.ping(request).await {
+                eprintln!("worker heartbeat to {} failed: {}", primary_addr, e);
+            }
+        }
+    });
+
+    crate::server::run_server(&listen_addr).await
+}
+
+/// Registers `files` to run on a recurring interval against `server_addr`,
+/// returning the schedule entry id. `overlap_policy` is `"skip"` or
+/// `"queue"`, defaulting server-side to `"skip"` for anything else.
+pub async fn schedule_job(
+    server_addr: String,
+    files: Vec<String>,
+    map_function: String,
+    reduce_function: String,
+    interval_secs: u64,
+    max_retries: u32,
+    overlap_policy: String,
+) -> Result<String> {
+    let mut client = MapReduceServiceClient::connect(server_addr.clone()).await?;
+    let request = Request::new(ScheduleJobRequest {
+        server_addr,
+        files,
+        map_function,
+        reduce_function,
+        interval_secs,
+        max_retries,
+        overlap_policy,
+    });
+    let response = client.schedule_job(request).await?;
+    Ok(response.into_inner().schedule_id)
+}
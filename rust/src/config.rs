@@ -1,14 +1,117 @@
 use std::env;
 use num_cpus;
 
+/// Credentials and endpoint for talking to an S3-compatible object store.
+#[derive(Debug, Clone, Default)]
+pub struct S3Config {
+    pub endpoint: Option<String>,
+    pub region: String,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+}
+
+impl S3Config {
+    pub fn from_env() -> Self {
+        Self {
+            endpoint: env::var("MR_S3_ENDPOINT").ok(),
+            region: env::var("MR_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key: env::var("MR_S3_ACCESS_KEY").ok(),
+            secret_key: env::var("MR_S3_SECRET_KEY").ok(),
+        }
+    }
+}
+
+/// Default prefix for Fluentd event tags.
+const DEFAULT_FLUENTD_TAG_PREFIX: &str = "mapreduce";
+
+/// Default bounds for content-defined chunking: 1 MiB to 8 MiB per chunk.
+const DEFAULT_MIN_CHUNK_SIZE: u64 = 1024 * 1024;
+const DEFAULT_MAX_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Which transport moves map/reduce tasks between the client and workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportBackend {
+    /// Direct, point-to-point gRPC calls (the default).
+    Grpc,
+    /// Durable work-queue dispatch over NATS JetStream.
+    Jetstream,
+}
+
+impl TransportBackend {
+    fn from_env() -> Self {
+        match env::var("MR_TRANSPORT").ok().as_deref() {
+            Some("jetstream") => TransportBackend::Jetstream,
+            _ => TransportBackend::Grpc,
+        }
+    }
+}
+
+/// Which embedded database backend persists coordinator task state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistenceBackend {
+    /// Embedded key-value store (the default).
+    Sled,
+    /// Embedded relational store.
+    Sqlite,
+}
+
+impl PersistenceBackend {
+    fn from_env() -> Self {
+        match env::var("MR_PERSISTENCE_BACKEND").ok().as_deref() {
+            Some("sqlite") => PersistenceBackend::Sqlite,
+            _ => PersistenceBackend::Sled,
+        }
+    }
+}
+
+/// Default size of the blocking-task pool that CPU-bound map/reduce
+/// invocations run on, scaled with core count the same way `worker_threads`
+/// is rather than reusing Tokio's much larger general-purpose default.
+fn default_blocking_pool_size() -> usize {
+    num_cpus::get() * 4
+}
+
 pub struct Config {
     pub worker_threads: usize,
+    pub num_reducers: usize,
+    pub min_chunk_size: u64,
+    pub max_chunk_size: u64,
+    pub s3: S3Config,
+    pub transport: TransportBackend,
+    pub nats_url: String,
+    /// `host:port` of a Fluentd forward collector; `None` disables structured
+    /// event logging entirely.
+    pub fluentd_addr: Option<String>,
+    /// Prepended to every event tag sent to `fluentd_addr`, e.g.
+    /// `"mapreduce"` yields `mapreduce.worker.registered`.
+    pub fluentd_tag_prefix: String,
+    /// Max threads in the `spawn_blocking` pool that map/reduce invocations
+    /// run on, so large inputs can't starve the async runtime's own worker
+    /// threads (and with them the heartbeat/health-check loops).
+    pub blocking_pool_size: usize,
+    /// Filesystem path for the coordinator's task-state database; `None`
+    /// keeps the coordinator purely in-memory, so a crash loses job
+    /// progress.
+    pub persistence_path: Option<String>,
+    /// Which embedded database backend `persistence_path` is opened with.
+    pub persistence_backend: PersistenceBackend,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             worker_threads: num_cpus::get(),
+            num_reducers: num_cpus::get(),
+            min_chunk_size: DEFAULT_MIN_CHUNK_SIZE,
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            s3: S3Config::default(),
+            transport: TransportBackend::Grpc,
+            nats_url: "nats://localhost:4222".to_string(),
+            fluentd_addr: None,
+            fluentd_tag_prefix: DEFAULT_FLUENTD_TAG_PREFIX.to_string(),
+            blocking_pool_size: default_blocking_pool_size(),
+            persistence_path: None,
+            persistence_backend: PersistenceBackend::Sled,
         }
     }
 }
@@ -20,6 +123,46 @@ impl Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or_else(|| num_cpus::get());
 
-        Self { worker_threads }
+        let num_reducers = env::var("MR_NUM_REDUCERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(worker_threads);
+
+        let min_chunk_size = env::var("MR_MIN_CHUNK_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MIN_CHUNK_SIZE);
+
+        let max_chunk_size = env::var("MR_MAX_CHUNK_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CHUNK_SIZE);
+
+        let nats_url = env::var("MR_NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+        let fluentd_addr = env::var("MR_FLUENTD_ADDR").ok();
+        let fluentd_tag_prefix = env::var("MR_FLUENTD_TAG_PREFIX")
+            .unwrap_or_else(|_| DEFAULT_FLUENTD_TAG_PREFIX.to_string());
+
+        let blocking_pool_size = env::var("MR_BLOCKING_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(default_blocking_pool_size);
+
+        let persistence_path = env::var("MR_PERSISTENCE_PATH").ok();
+
+        Self {
+            worker_threads,
+            num_reducers,
+            min_chunk_size,
+            max_chunk_size,
+            s3: S3Config::from_env(),
+            transport: TransportBackend::from_env(),
+            nats_url,
+            fluentd_addr,
+            fluentd_tag_prefix,
+            blocking_pool_size,
+            persistence_path,
+            persistence_backend: PersistenceBackend::from_env(),
+        }
     }
 }
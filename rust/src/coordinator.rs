@@ -1,37 +1,100 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tokio::sync::broadcast;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
 use uuid::Uuid;
 use num_cpus;
 
-#[derive(Debug, Clone)]
+use crate::background_worker::{WorkerHandle, WorkerState};
+use crate::fluentd::{self, FluentdSink};
+use crate::scheduler::{Scheduler, ScheduleEntry, ScheduleSpec};
+use crate::storage::{NullTaskStore, TaskStore};
+
+/// How long a worker can go without a heartbeat before it's considered dead.
+const HEARTBEAT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long a terminal task (`Completed`/`Failed`/`Cancelled`) stays in
+/// [`Coordinator::tasks`] after finishing, so a client polling job status
+/// shortly after completion still finds it, before [`Coordinator::evict_finished_tasks`]
+/// drops it. Without this, `self.tasks` grows for as long as the process runs.
+const TASK_RETENTION: std::time::Duration = std::time::Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TaskStatus {
     Pending,
     InProgress,
     Completed,
     Failed,
+    /// Stopped by an explicit `cancel_job`, as opposed to failing on its own.
+    Cancelled,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: String,
+    pub job_id: String,
     pub status: TaskStatus,
     pub worker_id: Option<String>,
     pub retries: u32,
 }
 
+/// A pause/resume/cancel command for one job's in-flight tasks. Workers
+/// watch a job's channel and act on the latest value between task units,
+/// rather than being killed outright.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobControl {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// Everything a worker needs to know about a job's runtime control state:
+/// the pause/resume/cancel command plus its current tranquility, bundled
+/// together since both are carried on the same watch channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobControlState {
+    pub command: JobControl,
+    /// Passed to `Tranquilizer::tranquilize` between task units; `0` (the
+    /// default) runs flat-out.
+    pub tranquility: u32,
+}
+
+impl Default for JobControlState {
+    fn default() -> Self {
+        Self { command: JobControl::Resume, tranquility: 0 }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Worker {
     pub id: String,
     pub address: String,
     pub last_heartbeat: std::time::SystemTime,
     pub tasks: Vec<String>,
+    pub state: WorkerState,
+}
+
+/// A point-in-time liveness summary for one worker, as returned by
+/// [`Coordinator::list_workers`] so an operator can tell active workers from
+/// idle ones and from ones that have gone dead.
+#[derive(Debug, Clone)]
+pub struct WorkerInfo {
+    pub id: String,
+    pub address: String,
+    pub state: WorkerState,
 }
 
 pub struct Coordinator {
     workers: Arc<Mutex<HashMap<String, Worker>>>,
     tasks: Arc<Mutex<HashMap<String, Task>>>,
+    /// When each terminal task finished, so [`Self::evict_finished_tasks`]
+    /// knows which entries in `tasks` are past [`TASK_RETENTION`].
+    terminal_since: Arc<Mutex<HashMap<String, std::time::SystemTime>>>,
     task_updates: broadcast::Sender<String>,
+    job_controls: Arc<Mutex<HashMap<String, watch::Sender<JobControlState>>>>,
+    task_store: Arc<dyn TaskStore>,
+    fluentd: FluentdSink,
+    scheduler: Arc<Scheduler>,
 }
 
 impl Default for Coordinator {
@@ -40,7 +103,12 @@ impl Default for Coordinator {
         Self {
             workers: Arc::new(Mutex::new(HashMap::new())),
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            terminal_since: Arc::new(Mutex::new(HashMap::new())),
             task_updates: tx,
+            job_controls: Arc::new(Mutex::new(HashMap::new())),
+            task_store: Arc::new(NullTaskStore),
+            fluentd: FluentdSink::NoOp,
+            scheduler: Arc::new(Scheduler::new()),
         }
     }
 }
@@ -55,23 +123,112 @@ impl Coordinator {
         Self {
             workers: Arc::new(Mutex::new(HashMap::with_capacity(thread_count))),
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            terminal_since: Arc::new(Mutex::new(HashMap::new())),
+            task_updates: tx,
+            job_controls: Arc::new(Mutex::new(HashMap::new())),
+            task_store: Arc::new(NullTaskStore),
+            fluentd: FluentdSink::NoOp,
+            scheduler: Arc::new(Scheduler::new()),
+        }
+    }
+
+    /// Like [`Self::new_with_threads`], but backed by `task_store` instead
+    /// of an in-memory-only [`NullTaskStore`]. Reloads every persisted task
+    /// and marks any found `InProgress` back to `Pending` with an
+    /// incremented retry count, since whatever worker was running it is
+    /// gone now that the coordinator itself is starting up — this is what
+    /// lets a crashed server resume a job instead of losing its progress.
+    pub fn new_with_store(thread_count: usize, task_store: Arc<dyn TaskStore>) -> Self {
+        let (tx, _) = broadcast::channel(100);
+        let mut tasks = HashMap::new();
+        let mut terminal_since = HashMap::new();
+
+        match task_store.load_all_tasks() {
+            Ok(loaded) => {
+                for mut task in loaded {
+                    if matches!(task.status, TaskStatus::InProgress) {
+                        task.status = TaskStatus::Pending;
+                        task.worker_id = None;
+                        task.retries += 1;
+                        if let Err(e) = task_store.save_task(&task) {
+                            eprintln!("failed to persist recovered task state: {e}");
+                        }
+                    } else if matches!(task.status, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled) {
+                        terminal_since.insert(task.id.clone(), std::time::SystemTime::now());
+                    }
+                    tasks.insert(task.id.clone(), task);
+                }
+            }
+            Err(e) => eprintln!("failed to reload persisted tasks, starting empty: {e}"),
+        }
+
+        Self {
+            workers: Arc::new(Mutex::new(HashMap::with_capacity(thread_count))),
+            tasks: Arc::new(Mutex::new(tasks)),
+            terminal_since: Arc::new(Mutex::new(terminal_since)),
             task_updates: tx,
+            job_controls: Arc::new(Mutex::new(HashMap::new())),
+            task_store,
+            fluentd: FluentdSink::NoOp,
+            scheduler: Arc::new(Scheduler::new()),
         }
     }
 
+    /// Attaches a Fluentd sink so worker/task lifecycle events get forwarded
+    /// as they happen, rather than only being observable through assertions
+    /// in tests.
+    pub fn with_fluentd(mut self, fluentd: FluentdSink) -> Self {
+        self.fluentd = fluentd;
+        self
+    }
+
+    /// Fires `tag`'s event on a detached task so emitting it (which involves
+    /// a network write) never blocks the caller or holds up whatever
+    /// coordinator lock is held at the call site.
+    fn emit_event(&self, tag: &str, fields: HashMap<String, String>) {
+        let fluentd = self.fluentd.clone();
+        let tag = tag.to_string();
+        tokio::spawn(async move {
+            fluentd.emit(&tag, fields).await;
+        });
+    }
+
     pub fn register_worker(&self, address: String) -> String {
         let worker_id = Uuid::new_v4().to_string();
         let worker = Worker {
             id: worker_id.clone(),
-            address,
+            address: address.clone(),
             last_heartbeat: std::time::SystemTime::now(),
             tasks: Vec::new(),
+            state: WorkerState::Idle { wait_until: std::time::SystemTime::now() + HEARTBEAT_TIMEOUT },
         };
 
         self.workers.lock().unwrap().insert(worker_id.clone(), worker);
+        self.emit_event("worker.registered", fluentd::fields(&[("worker_id", &worker_id), ("address", &address)]));
         worker_id
     }
 
+    /// Creates a new `Pending` task for `job_id` and returns its id, so a
+    /// real execution path (e.g. the gRPC `map`/`reduce` handlers) has
+    /// something for [`Self::assign_task`]/[`Self::update_task_status`] to
+    /// act on, instead of that bookkeeping only ever being exercised by
+    /// constructing `Task`s directly in tests.
+    pub fn create_task(&self, job_id: &str) -> String {
+        let task = Task {
+            id: Uuid::new_v4().to_string(),
+            job_id: job_id.to_string(),
+            status: TaskStatus::Pending,
+            worker_id: None,
+            retries: 0,
+        };
+        let task_id = task.id.clone();
+        if let Err(e) = self.task_store.save_task(&task) {
+            eprintln!("failed to persist task state: {e}");
+        }
+        self.tasks.lock().unwrap().insert(task_id.clone(), task);
+        task_id
+    }
+
     pub fn assign_task(&self, task_id: String, worker_id: String) -> bool {
         let mut tasks = self.tasks.lock().unwrap();
         let mut workers = self.workers.lock().unwrap();
@@ -80,7 +237,12 @@ impl Coordinator {
             if let Some(worker) = workers.get_mut(&worker_id) {
                 task.status = TaskStatus::InProgress;
                 task.worker_id = Some(worker_id.clone());
-                worker.tasks.push(task_id);
+                if let Err(e) = self.task_store.save_task(task) {
+                    eprintln!("failed to persist task state: {e}");
+                }
+                worker.tasks.push(task_id.clone());
+                worker.state = WorkerState::Busy;
+                self.emit_event("task.assigned", fluentd::fields(&[("task_id", &task_id), ("worker_id", &worker_id)]));
                 return true;
             }
         }
@@ -90,7 +252,33 @@ impl Coordinator {
     pub fn update_task_status(&self, task_id: String, status: TaskStatus) {
         let mut tasks = self.tasks.lock().unwrap();
         if let Some(task) = tasks.get_mut(&task_id) {
+            let worker_id = task.worker_id.clone();
             task.status = status;
+            if let Err(e) = self.task_store.save_task(task) {
+                eprintln!("failed to persist task state: {e}");
+            }
+
+            match task.status {
+                TaskStatus::Completed => self.emit_event("task.completed", fluentd::fields(&[("task_id", &task_id)])),
+                TaskStatus::Failed => self.emit_event("task.failed", fluentd::fields(&[("task_id", &task_id)])),
+                _ => {}
+            }
+
+            // A task reaching a terminal state frees its worker back up, and
+            // is dropped from the worker's task list so a later stale
+            // heartbeat in `check_worker_health` never finds it there to
+            // reset back to `Pending`.
+            if matches!(task.status, TaskStatus::Completed | TaskStatus::Failed) {
+                if let Some(worker_id) = worker_id {
+                    let mut workers = self.workers.lock().unwrap();
+                    if let Some(worker) = workers.get_mut(&worker_id) {
+                        worker.state = WorkerState::Idle { wait_until: std::time::SystemTime::now() + HEARTBEAT_TIMEOUT };
+                        worker.tasks.retain(|id| id != &task_id);
+                    }
+                }
+                self.terminal_since.lock().unwrap().insert(task_id.clone(), std::time::SystemTime::now());
+            }
+
             let _ = self.task_updates.send(task_id);
         }
     }
@@ -99,32 +287,297 @@ impl Coordinator {
         let mut workers = self.workers.lock().unwrap();
         if let Some(worker) = workers.get_mut(&worker_id) {
             worker.last_heartbeat = std::time::SystemTime::now();
+            // A heartbeat from a worker that isn't mid-task refreshes its
+            // idle deadline; a dead worker that checks back in is revived.
+            if !matches!(worker.state, WorkerState::Busy) {
+                worker.state = WorkerState::Idle { wait_until: worker.last_heartbeat + HEARTBEAT_TIMEOUT };
+            }
+            self.emit_event("worker.heartbeat", fluentd::fields(&[("worker_id", &worker_id)]));
             return true;
         }
         false
     }
 
-    pub fn check_worker_health(&self) {
+    pub fn worker_count(&self) -> usize {
+        self.workers.lock().unwrap().len()
+    }
+
+    /// Workers that are still considered alive (`Busy` or `Idle`), as
+    /// opposed to ones that have been marked `Done` by
+    /// [`Self::check_worker_health`].
+    pub fn healthy_worker_count(&self) -> usize {
+        let workers = self.workers.lock().unwrap();
+        workers.values().filter(|w| !matches!(w.state, WorkerState::Done)).count()
+    }
+
+    /// Lists every registered worker's current liveness, including ones that
+    /// have gone dead, so an operator can tell active workers from idle ones
+    /// from dead ones instead of dead workers just silently disappearing.
+    pub fn list_workers(&self) -> Vec<WorkerInfo> {
+        self.workers
+            .lock()
+            .unwrap()
+            .values()
+            .map(|w| WorkerInfo { id: w.id.clone(), address: w.address.clone(), state: w.state.clone() })
+            .collect()
+    }
+
+    /// Marks workers whose heartbeat has gone stale as `Done` and reassigns
+    /// their in-flight tasks back to `Pending`, rather than silently evicting
+    /// them, so their lifecycle stays observable via [`Self::list_workers`].
+    /// Returns the newly-dead workers' ids so callers can report them (e.g.
+    /// as `worker.unhealthy`); each reassigned task is reported from here as
+    /// a `task.reassigned` event.
+    pub fn check_worker_health(&self) -> Vec<String> {
         let mut workers = self.workers.lock().unwrap();
         let mut tasks = self.tasks.lock().unwrap();
-        
-        let timeout = std::time::Duration::from_secs(30);
+
         let now = std::time::SystemTime::now();
+        let mut newly_dead = Vec::new();
 
-        workers.retain(|_worker_id, worker| {
-            if now.duration_since(worker.last_heartbeat).unwrap() > timeout {
-                // Reassign tasks from failed worker
+        for (worker_id, worker) in workers.iter_mut() {
+            if matches!(worker.state, WorkerState::Done) {
+                continue;
+            }
+            if now.duration_since(worker.last_heartbeat).unwrap() > HEARTBEAT_TIMEOUT {
+                worker.state = WorkerState::Done;
                 for task_id in &worker.tasks {
-                    if let Some(task) = tasks.get_mut(task_id) {
-                        task.status = TaskStatus::Pending;
-                        task.worker_id = None;
-                        task.retries += 1;
+                    // Only a task this worker is still actually running needs
+                    // reassigning; one that already reached a terminal status
+                    // (e.g. from a gRPC call that completed long before the
+                    // worker went stale) must not be reset back to `Pending`.
+                    let Some(task) = tasks.get_mut(task_id) else { continue };
+                    if !matches!(task.status, TaskStatus::InProgress) {
+                        continue;
                     }
+                    task.status = TaskStatus::Pending;
+                    task.worker_id = None;
+                    task.retries += 1;
+                    if let Err(e) = self.task_store.save_task(task) {
+                        eprintln!("failed to persist reassigned task state: {e}");
+                    }
+                    self.emit_event(
+                        "task.reassigned",
+                        fluentd::fields(&[
+                            ("task_id", task_id),
+                            ("worker_id", worker_id),
+                            ("retries", &task.retries.to_string()),
+                        ]),
+                    );
                 }
-                false
-            } else {
-                true
+                // Every task id above is either reassigned away from this
+                // worker or was already terminal; either way this worker
+                // shouldn't keep carrying it once it's marked `Done`.
+                worker.tasks.clear();
+                newly_dead.push(worker_id.clone());
             }
-        });
+        }
+
+        newly_dead
+    }
+
+    /// Gets or creates the control channel for `job_id`, defaulting to
+    /// `Resume` at tranquility `0` so a worker can watch a job before anyone
+    /// has paused, cancelled, or throttled it.
+    fn job_sender(&self, job_id: &str) -> watch::Sender<JobControlState> {
+        self.job_controls
+            .lock()
+            .unwrap()
+            .entry(job_id.to_string())
+            .or_insert_with(|| watch::channel(JobControlState::default()).0)
+            .clone()
+    }
+
+    /// Subscribes to `job_id`'s control channel. Assigned workers select on
+    /// this between task units to learn about pause/resume/cancel commands
+    /// and tranquility changes without the coordinator having to reach into
+    /// their task loop.
+    pub fn watch_job(&self, job_id: &str) -> watch::Receiver<JobControlState> {
+        self.job_sender(job_id).subscribe()
+    }
+
+    pub fn pause_job(&self, job_id: &str) {
+        self.job_sender(job_id).send_modify(|s| s.command = JobControl::Pause);
+    }
+
+    pub fn resume_job(&self, job_id: &str) {
+        self.job_sender(job_id).send_modify(|s| s.command = JobControl::Resume);
+    }
+
+    /// Cancels `job_id`: signals its control channel and marks every one of
+    /// its `Pending`/`InProgress` tasks `Cancelled`, distinct from `Failed`
+    /// since nothing about the task itself went wrong.
+    pub fn cancel_job(&self, job_id: &str) {
+        self.job_sender(job_id).send_modify(|s| s.command = JobControl::Cancel);
+
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut terminal_since = self.terminal_since.lock().unwrap();
+        for task in tasks.values_mut() {
+            if task.job_id == job_id && matches!(task.status, TaskStatus::Pending | TaskStatus::InProgress) {
+                task.status = TaskStatus::Cancelled;
+                if let Err(e) = self.task_store.save_task(task) {
+                    eprintln!("failed to persist cancelled task state: {e}");
+                }
+                terminal_since.insert(task.id.clone(), std::time::SystemTime::now());
+            }
+        }
+    }
+
+    /// Drops every task that reached a terminal state more than
+    /// [`TASK_RETENTION`] ago from the in-memory map, so a long-running
+    /// server's `tasks` table doesn't grow for as long as the process lives.
+    /// Meant to be swept periodically (see `TaskReaper` in `server.rs`), not
+    /// called inline from a hot path.
+    pub fn evict_finished_tasks(&self) -> usize {
+        let mut terminal_since = self.terminal_since.lock().unwrap();
+        let now = std::time::SystemTime::now();
+        let expired: Vec<String> = terminal_since
+            .iter()
+            .filter(|(_, &since)| now.duration_since(since).unwrap_or_default() > TASK_RETENTION)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        if expired.is_empty() {
+            return 0;
+        }
+
+        let mut tasks = self.tasks.lock().unwrap();
+        for task_id in &expired {
+            tasks.remove(task_id);
+            terminal_since.remove(task_id);
+        }
+        expired.len()
+    }
+
+    /// Adjusts `job_id`'s tranquility at runtime, read by workers via the
+    /// same control channel between task units.
+    pub fn set_tranquility(&self, job_id: &str, tranquility: u32) {
+        self.job_sender(job_id).send_modify(|s| s.tranquility = tranquility);
+    }
+
+    /// Registers a recurring job and returns its schedule entry id.
+    pub fn register_schedule(&self, spec: ScheduleSpec) -> String {
+        self.scheduler.register(spec)
+    }
+
+    pub fn pause_schedule(&self, id: &str) {
+        self.scheduler.pause(id);
+    }
+
+    pub fn resume_schedule(&self, id: &str) {
+        self.scheduler.resume(id);
+    }
+
+    pub fn remove_schedule(&self, id: &str) {
+        self.scheduler.remove(id);
+    }
+
+    pub fn list_schedules(&self) -> Vec<ScheduleEntry> {
+        self.scheduler.list()
+    }
+
+    /// Spawns the tick loop that fires due schedule entries through
+    /// `client::run_map_reduce_job`, returning a handle the caller can poll
+    /// via `WorkerHandle::status`.
+    pub fn spawn_scheduler(&self) -> WorkerHandle {
+        Arc::clone(&self.scheduler).spawn()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(job_id: &str, status: TaskStatus) -> Task {
+        Task { id: Uuid::new_v4().to_string(), job_id: job_id.to_string(), status, worker_id: None, retries: 0 }
+    }
+
+    #[test]
+    fn cancel_job_only_transitions_pending_and_in_progress_tasks() {
+        let coordinator = Coordinator::default();
+        let job_id = "job-1";
+
+        let pending = task(job_id, TaskStatus::Pending);
+        let in_progress = task(job_id, TaskStatus::InProgress);
+        let completed = task(job_id, TaskStatus::Completed);
+        let failed = task(job_id, TaskStatus::Failed);
+        let (pending_id, in_progress_id, completed_id, failed_id) =
+            (pending.id.clone(), in_progress.id.clone(), completed.id.clone(), failed.id.clone());
+
+        {
+            let mut tasks = coordinator.tasks.lock().unwrap();
+            for t in [pending, in_progress, completed, failed] {
+                tasks.insert(t.id.clone(), t);
+            }
+        }
+
+        coordinator.cancel_job(job_id);
+
+        let tasks = coordinator.tasks.lock().unwrap();
+        assert!(matches!(tasks[&pending_id].status, TaskStatus::Cancelled));
+        assert!(matches!(tasks[&in_progress_id].status, TaskStatus::Cancelled));
+        assert!(matches!(tasks[&completed_id].status, TaskStatus::Completed));
+        assert!(matches!(tasks[&failed_id].status, TaskStatus::Failed));
+    }
+
+    #[test]
+    fn pause_resume_cancel_update_the_watch_channel() {
+        let coordinator = Coordinator::default();
+        let job_id = "job-1";
+        let watcher = coordinator.watch_job(job_id);
+
+        coordinator.pause_job(job_id);
+        assert_eq!(watcher.borrow().command, JobControl::Pause);
+
+        coordinator.resume_job(job_id);
+        assert_eq!(watcher.borrow().command, JobControl::Resume);
+
+        coordinator.cancel_job(job_id);
+        assert_eq!(watcher.borrow().command, JobControl::Cancel);
+    }
+
+    #[test]
+    fn set_tranquility_updates_tranquility_without_touching_command() {
+        let coordinator = Coordinator::default();
+        let job_id = "job-1";
+        let watcher = coordinator.watch_job(job_id);
+
+        coordinator.pause_job(job_id);
+        coordinator.set_tranquility(job_id, 5);
+
+        let state = *watcher.borrow();
+        assert_eq!(state.command, JobControl::Pause);
+        assert_eq!(state.tranquility, 5);
+    }
+
+    #[test]
+    fn evict_finished_tasks_drops_only_tasks_past_retention() {
+        let coordinator = Coordinator::default();
+
+        let stale = task("job-1", TaskStatus::Completed);
+        let fresh = task("job-1", TaskStatus::Failed);
+        let in_progress = task("job-1", TaskStatus::InProgress);
+        let (stale_id, fresh_id, in_progress_id) =
+            (stale.id.clone(), fresh.id.clone(), in_progress.id.clone());
+
+        {
+            let mut tasks = coordinator.tasks.lock().unwrap();
+            for t in [stale, fresh, in_progress] {
+                tasks.insert(t.id.clone(), t);
+            }
+        }
+        {
+            let mut terminal_since = coordinator.terminal_since.lock().unwrap();
+            terminal_since.insert(stale_id.clone(), std::time::SystemTime::now() - TASK_RETENTION - std::time::Duration::from_secs(1));
+            terminal_since.insert(fresh_id.clone(), std::time::SystemTime::now());
+        }
+
+        let evicted = coordinator.evict_finished_tasks();
+
+        assert_eq!(evicted, 1);
+        let tasks = coordinator.tasks.lock().unwrap();
+        assert!(!tasks.contains_key(&stale_id));
+        assert!(tasks.contains_key(&fresh_id));
+        assert!(tasks.contains_key(&in_progress_id));
     }
 }
@@ -0,0 +1,108 @@
+//! Structured job-lifecycle logging to a Fluentd collector. Events are
+//! forwarded as MessagePack records over the Fluentd forward protocol so
+//! many worker hosts can land their events in one searchable stream instead
+//! of scattered stdout. When no collector is configured, emission is a
+//! no-op.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rmp_serde::Serializer;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Result<T> = std::result::Result<T, Error>;
+
+/// A single structured event, shaped as the Fluentd forward protocol's
+/// `[tag, time, record]` entry.
+#[derive(Serialize)]
+struct ForwardEntry {
+    tag: String,
+    time: u64,
+    record: HashMap<String, String>,
+}
+
+/// Default prefix applied to event tags when a sink is built without an
+/// explicit one (mirrors `Config`'s own default).
+const DEFAULT_TAG_PREFIX: &str = "mapreduce";
+
+#[derive(Clone)]
+pub enum FluentdSink {
+    Forward {
+        addr: String,
+        tag_prefix: String,
+        conn: Arc<Mutex<Option<TcpStream>>>,
+    },
+    NoOp,
+}
+
+impl FluentdSink {
+    /// Reads `MR_FLUENTD_ADDR` (mirroring `MR_WORKER_THREADS`'s env-driven
+    /// config) for a `host:port` Fluentd forward endpoint; falls back to a
+    /// no-op sink when unset.
+    pub fn from_env() -> Self {
+        match std::env::var("MR_FLUENTD_ADDR") {
+            Ok(addr) => FluentdSink::Forward {
+                addr,
+                tag_prefix: DEFAULT_TAG_PREFIX.to_string(),
+                conn: Arc::new(Mutex::new(None)),
+            },
+            Err(_) => FluentdSink::NoOp,
+        }
+    }
+
+    /// Builds a sink from `Config`'s `fluentd_addr`/`fluentd_tag_prefix`,
+    /// falling back to a no-op sink when no endpoint is configured.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        match &config.fluentd_addr {
+            Some(addr) => FluentdSink::Forward {
+                addr: addr.clone(),
+                tag_prefix: config.fluentd_tag_prefix.clone(),
+                conn: Arc::new(Mutex::new(None)),
+            },
+            None => FluentdSink::NoOp,
+        }
+    }
+
+    /// Emits an event tagged `<tag_prefix>.<tag>`, e.g. `mapreduce.job.start`
+    /// for `tag = "job.start"`, with its fields. Connection failures are
+    /// logged and otherwise swallowed so a missing collector never fails the
+    /// call that triggered the event.
+    pub async fn emit(&self, tag: &str, fields: HashMap<String, String>) {
+        let FluentdSink::Forward { addr, tag_prefix, conn } = self else { return };
+        let tag = format!("{tag_prefix}.{tag}");
+        if let Err(e) = Self::send(addr, conn, &tag, fields).await {
+            eprintln!("fluentd: failed to emit '{}': {}", tag, e);
+        }
+    }
+
+    async fn send(addr: &str, conn: &Mutex<Option<TcpStream>>, tag: &str, fields: HashMap<String, String>) -> Result<()> {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut buf = Vec::new();
+        ForwardEntry { tag: tag.to_string(), time, record: fields }.serialize(&mut Serializer::new(&mut buf))?;
+
+        let mut guard = conn.lock().await;
+        if guard.is_none() {
+            *guard = Some(TcpStream::connect(addr).await?);
+        }
+        let stream = guard.as_mut().expect("just connected");
+        if let Err(e) = stream.write_all(&buf).await {
+            // Drop the stale connection so the next emit reconnects.
+            *guard = None;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+/// Small builder so call sites read as a list of fields rather than a
+/// `HashMap::from([...])` literal.
+pub fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
@@ -0,0 +1,338 @@
+//! Abstracts over where map-phase input bytes live so `MapReduceService::map` and
+//! `client::get_txt_files` don't need to care whether a path points at the local
+//! disk or an object in S3-compatible storage.
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, BufReader};
+
+use crate::config::S3Config;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Result<T> = std::result::Result<T, Error>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// A line-oriented reader over an input, regardless of where its bytes came from.
+pub type LineReader = BufReader<Pin<Box<dyn AsyncRead + Send>>>;
+
+fn into_line_reader<R: AsyncRead + Send + 'static>(reader: R) -> LineReader {
+    BufReader::new(Box::pin(reader))
+}
+
+/// Where a `MapRequest.file_path` points: the local filesystem, or an object
+/// in S3-compatible storage addressed as `s3://bucket/key`.
+#[async_trait::async_trait]
+pub trait InputSource: Send + Sync {
+    /// Open a streaming reader positioned at the start of `path`.
+    async fn open(&self, path: &str) -> Result<LineReader> {
+        self.open_range(path, 0, None).await
+    }
+
+    /// Open a streaming reader over just `[offset, offset + length)` of
+    /// `path` (or `[offset, EOF)` when `length` is `None`), so a worker that
+    /// was only handed one content-defined chunk of a large file doesn't
+    /// have to read the rest of it.
+    async fn open_range(&self, path: &str, offset: u64, length: Option<u64>) -> Result<LineReader>;
+
+    /// Expand a directory/prefix into the paths it contains. The local source
+    /// lists a directory's direct entries only; the S3 source returns every
+    /// key under the prefix, including keys below further `/` separators.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>>;
+}
+
+/// Reads `file://` URIs and bare filesystem paths via `tokio::fs`.
+pub struct LocalInputSource;
+
+#[async_trait::async_trait]
+impl InputSource for LocalInputSource {
+    async fn open_range(&self, path: &str, offset: u64, length: Option<u64>) -> Result<LineReader> {
+        let path = path.strip_prefix("file://").unwrap_or(path);
+        let mut file = tokio::fs::File::open(path).await?;
+        if offset > 0 {
+            file.seek(std::io::SeekFrom::Start(offset)).await?;
+        }
+        match length {
+            Some(len) => Ok(into_line_reader(file.take(len))),
+            None => Ok(into_line_reader(file)),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let prefix = prefix.strip_prefix("file://").unwrap_or(prefix);
+        let mut out = Vec::new();
+        let mut entries = tokio::fs::read_dir(prefix).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(path_str) = entry.path().to_str() {
+                out.push(path_str.to_string());
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Reads `s3://bucket/key` URIs straight off the S3 HTTP API, streaming the
+/// object body into the line reader instead of buffering it whole.
+pub struct S3InputSource {
+    client: reqwest::Client,
+    config: S3Config,
+}
+
+impl S3InputSource {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn parse_uri(uri: &str) -> Result<(String, String)> {
+        let rest = uri
+            .strip_prefix("s3://")
+            .ok_or_else(|| format!("not an s3:// URI: {uri}"))?;
+        let (bucket, key) = rest
+            .split_once('/')
+            .ok_or_else(|| format!("s3 URI missing key: {uri}"))?;
+        Ok((bucket.to_string(), key.to_string()))
+    }
+
+    fn object_url(&self, bucket: &str, key: &str) -> String {
+        match &self.config.endpoint {
+            Some(endpoint) => format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, key),
+            None => format!("https://{}.s3.{}.amazonaws.com/{}", bucket, self.config.region, key),
+        }
+    }
+
+    fn bucket_url(&self, bucket: &str) -> String {
+        match &self.config.endpoint {
+            Some(endpoint) => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+            None => format!("https://{}.s3.{}.amazonaws.com", bucket, self.config.region),
+        }
+    }
+
+    /// Minimal AWS SigV4 signing for unsigned-payload GET requests, enough to
+    /// authenticate object GETs and ListObjectsV2 calls against S3 or a
+    /// SigV4-compatible endpoint (e.g. MinIO). Falls back to an unsigned
+    /// request when no credentials are configured.
+    fn sign(&self, req: reqwest::RequestBuilder, host: &str, path: &str, query: &[(&str, &str)]) -> reqwest::RequestBuilder {
+        let (access_key, secret_key) = match (&self.config.access_key, &self.config.secret_key) {
+            (Some(a), Some(s)) => (a.clone(), s.clone()),
+            _ => return req,
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = now.format("%Y%m%d").to_string();
+
+        let (authorization, amz_date) = build_authorization(
+            &access_key,
+            &secret_key,
+            &self.config.region,
+            host,
+            path,
+            query,
+            &amz_date,
+            &date_stamp,
+        );
+
+        req.header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", "UNSIGNED-PAYLOAD")
+            .header("Authorization", authorization)
+    }
+}
+
+/// Percent-encodes and sorts `params` into a SigV4 CanonicalQueryString
+/// (`key=value` pairs, `&`-joined, ordered by encoded key).
+fn canonical_query_string(params: &[(&str, &str)]) -> String {
+    let mut encoded: Vec<(String, String)> = params
+        .iter()
+        .map(|(k, v)| (urlencoding::encode(k).into_owned(), urlencoding::encode(v).into_owned()))
+        .collect();
+    encoded.sort();
+    encoded
+        .into_iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Builds the `Authorization` header value for a SigV4-signed, unsigned-payload
+/// GET request, per the `METHOD\nCanonicalURI\nCanonicalQueryString\n...`
+/// canonical request spec. Split out from `S3InputSource::sign` so the
+/// signing math can be exercised without a live clock or `S3Config`. Returns
+/// the authorization value alongside the `amz_date` it was computed for.
+fn build_authorization(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    host: &str,
+    path: &str,
+    query: &[(&str, &str)],
+    amz_date: &str,
+    date_stamp: &str,
+) -> (String, String) {
+    let credential_scope = format!("{date_stamp}/{region}/s3/aws4_request");
+    let canonical_headers = format!("host:{host}\nx-amz-content-sha256:UNSIGNED-PAYLOAD\nx-amz-date:{amz_date}\n");
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_query_string = canonical_query_string(query);
+    let canonical_request = format!(
+        "GET\n{path}\n{canonical_query_string}\n{canonical_headers}\n{signed_headers}\nUNSIGNED-PAYLOAD"
+    );
+    let hashed_request = hex::encode(Sha256::digest(canonical_request.as_bytes()));
+
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{credential_scope}\n{hashed_request}"
+    );
+
+    let sign = |key: &[u8], msg: &str| -> Vec<u8> {
+        let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts any key length");
+        mac.update(msg.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    };
+
+    let k_date = sign(format!("AWS4{secret_key}").as_bytes(), date_stamp);
+    let k_region = sign(&k_date, region);
+    let k_service = sign(&k_region, "s3");
+    let k_signing = sign(&k_service, "aws4_request");
+    let signature = hex::encode(sign(&k_signing, &string_to_sign));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={access_key}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}"
+    );
+    (authorization, amz_date.to_string())
+}
+
+#[async_trait::async_trait]
+impl InputSource for S3InputSource {
+    async fn open_range(&self, path: &str, offset: u64, length: Option<u64>) -> Result<LineReader> {
+        let (bucket, key) = Self::parse_uri(path)?;
+        let url = self.object_url(&bucket, &key);
+        // Sign the path the request is actually sent to: `/{key}` for the
+        // virtual-hosted AWS URL, `/{bucket}/{key}` for a path-style endpoint.
+        let parsed = reqwest::Url::parse(&url)?;
+        let host = parsed.host_str().ok_or("s3 endpoint missing host")?.to_string();
+        let path = parsed.path().to_string();
+
+        let mut req = self.sign(self.client.get(&url), &host, &path, &[]);
+        if offset > 0 || length.is_some() {
+            let range = match length {
+                Some(len) => format!("bytes={}-{}", offset, offset + len.saturating_sub(1)),
+                None => format!("bytes={}-", offset),
+            };
+            req = req.header("Range", range);
+        }
+
+        let response = req.send().await?.error_for_status()?;
+        let stream = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+        let reader = tokio_util::io::StreamReader::new(stream);
+        Ok(into_line_reader(reader))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let (bucket, key_prefix) = Self::parse_uri(prefix)?;
+        let url = format!(
+            "{}?list-type=2&prefix={}",
+            self.bucket_url(&bucket),
+            urlencoding::encode(&key_prefix)
+        );
+        let parsed = reqwest::Url::parse(&url)?;
+        let host = parsed.host_str().ok_or("s3 endpoint missing host")?.to_string();
+        let path = parsed.path().to_string();
+        let query = [("list-type", "2"), ("prefix", key_prefix.as_str())];
+
+        let req = self.sign(self.client.get(&url), &host, &path, &query);
+        let body = req.send().await?.error_for_status()?.text().await?;
+
+        // Minimal ListBucketResult parsing: pull out every <Key>...</Key>.
+        let mut keys = Vec::new();
+        let mut rest = body.as_str();
+        while let Some(start) = rest.find("<Key>") {
+            let after = &rest[start + "<Key>".len()..];
+            let Some(end) = after.find("</Key>") else { break };
+            keys.push(format!("s3://{bucket}/{}", &after[..end]));
+            rest = &after[end + "</Key>".len()..];
+        }
+        Ok(keys)
+    }
+}
+
+/// Picks the right `InputSource` for a path based on its URI scheme.
+pub fn resolve(path: &str, s3_config: &S3Config) -> Arc<dyn InputSource> {
+    if path.starts_with("s3://") {
+        Arc::new(S3InputSource::new(s3_config.clone()))
+    } else {
+        Arc::new(LocalInputSource)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test credentials and fixed clock from AWS's own SigV4 worked examples,
+    // so the canonical request this produces can be checked against a
+    // hand-computed expected signature rather than trusting the code under test.
+    const ACCESS_KEY: &str = "AKIDEXAMPLE";
+    const SECRET_KEY: &str = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+    const REGION: &str = "us-east-1";
+    const AMZ_DATE: &str = "20130524T000000Z";
+    const DATE_STAMP: &str = "20130524";
+
+    #[test]
+    fn canonical_query_string_percent_encodes_and_sorts_by_key() {
+        let params = [("prefix", "some dir/"), ("list-type", "2")];
+        assert_eq!(canonical_query_string(&params), "list-type=2&prefix=some%20dir%2F");
+    }
+
+    #[test]
+    fn canonical_query_string_of_no_params_is_empty() {
+        assert_eq!(canonical_query_string(&[]), "");
+    }
+
+    #[test]
+    fn build_authorization_matches_hand_computed_signature_for_a_get_object() {
+        let (authorization, amz_date) = build_authorization(
+            ACCESS_KEY,
+            SECRET_KEY,
+            REGION,
+            "examplebucket.s3.amazonaws.com",
+            "/test.txt",
+            &[],
+            AMZ_DATE,
+            DATE_STAMP,
+        );
+
+        assert_eq!(amz_date, AMZ_DATE);
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20130524/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=5c0d4ff29e72b8f94c5b6720369921e587e39bf7a64e456887dec4b43a2d1b77"
+        );
+    }
+
+    #[test]
+    fn build_authorization_sorts_and_encodes_query_params_for_a_list_objects_call() {
+        let (authorization, _) = build_authorization(
+            ACCESS_KEY,
+            SECRET_KEY,
+            REGION,
+            "examplebucket.s3.amazonaws.com",
+            "/examplebucket",
+            &[("prefix", "some dir/"), ("list-type", "2")],
+            AMZ_DATE,
+            DATE_STAMP,
+        );
+
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20130524/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=68457ca8e2faf7d5086546852bffffff79899fe4313ed5883ad60b4d59ac26b4"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Durable work-queue transport over NATS JetStream, selected as an
+//! alternative to the direct gRPC path via `Config::transport`. Map and
+//! reduce tasks are published to work-queue streams and pull-consumed with
+//! explicit ack, so a worker crash mid-task leaves the task to be
+//! redelivered rather than silently lost. Large intermediate partitions are
+//! staged in a JetStream object store instead of inlined into the task
+//! message.
+use serde::{Deserialize, Serialize};
+
+use crate::mapreduce::KeyValuePair;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Result<T> = std::result::Result<T, Error>;
+
+pub const MAP_SUBJECT: &str = "mr.map";
+pub const REDUCE_SUBJECT: &str = "mr.reduce";
+pub const MAP_STREAM: &str = "MR_MAP_TASKS";
+pub const REDUCE_STREAM: &str = "MR_REDUCE_TASKS";
+
+/// Redelivery deadline: a pulled message not acked within this window is
+/// handed back out by the server for another worker to pick up.
+const ACK_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Core-NATS (non-JetStream) subject a map worker publishes to once it's
+/// done staging a task's output, so the job submitter knows when to move on
+/// to the reduce phase without polling.
+fn map_done_subject(job_id: &str) -> String {
+    format!("mr.map.done.{job_id}")
+}
+
+/// Mirror of [`map_done_subject`] for reduce completions.
+fn reduce_done_subject(job_id: &str) -> String {
+    format!("mr.reduce.done.{job_id}")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapTask {
+    pub job_id: String,
+    /// Uniquely identifies this map task within `job_id`, so its staged
+    /// partitions never collide with another concurrently-running map
+    /// task's, and so reduce tasks know which object-store keys to look for.
+    pub task_id: String,
+    pub file_path: String,
+    pub offset: u64,
+    pub length: u64,
+    pub map_function: String,
+    /// How many reduce partitions this job's intermediate pairs are
+    /// hash-partitioned into, so a map worker can shuffle its own output
+    /// before staging it.
+    pub num_reducers: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReduceTask {
+    pub job_id: String,
+    pub partition: usize,
+    pub reduce_function: String,
+    /// Object-store keys that *may* hold this partition's intermediate
+    /// pairs, one per map task dispatched for this job. Not every map task
+    /// contributes to every partition, so a reduce worker loads whichever of
+    /// these actually exist and ignores the rest.
+    pub object_keys: Vec<String>,
+}
+
+/// Payload of a `mr.reduce.done.{job_id}` completion signal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReduceDone {
+    pub partition: usize,
+    pub result: String,
+}
+
+pub struct JetStreamTransport {
+    /// Raw NATS client, kept alongside the JetStream context so this
+    /// transport can also publish/subscribe on plain core-NATS subjects for
+    /// completion signals — JetStream's work queues are fire-and-forget from
+    /// the publisher's side and have no built-in way to report a result back.
+    client: async_nats::Client,
+    jetstream: async_nats::jetstream::Context,
+    object_store: async_nats::jetstream::object_store::ObjectStore,
+}
+
+impl JetStreamTransport {
+    /// Connects to `nats_url` and ensures the work-queue streams and object
+    /// store bucket this transport needs already exist.
+    pub async fn connect(nats_url: &str) -> Result<Self> {
+        let client = async_nats::connect(nats_url).await?;
+        let jetstream = async_nats::jetstream::new(client.clone());
+
+        jetstream
+            .get_or_create_stream(async_nats::jetstream::stream::Config {
+                name: MAP_STREAM.to_string(),
+                subjects: vec![MAP_SUBJECT.to_string()],
+                retention: async_nats::jetstream::stream::RetentionPolicy::WorkQueue,
+                ..Default::default()
+            })
+            .await?;
+
+        jetstream
+            .get_or_create_stream(async_nats::jetstream::stream::Config {
+                name: REDUCE_STREAM.to_string(),
+                subjects: vec![REDUCE_SUBJECT.to_string()],
+                retention: async_nats::jetstream::stream::RetentionPolicy::WorkQueue,
+                ..Default::default()
+            })
+            .await?;
+
+        let object_store = jetstream
+            .get_or_create_object_store(async_nats::jetstream::object_store::Config {
+                bucket: "mr-partitions".to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(Self { client, jetstream, object_store })
+    }
+
+    pub async fn publish_map_task(&self, task: &MapTask) -> Result<()> {
+        let payload = serde_json::to_vec(task)?;
+        self.jetstream.publish(MAP_SUBJECT, payload.into()).await?.await?;
+        Ok(())
+    }
+
+    pub async fn publish_reduce_task(&self, task: &ReduceTask) -> Result<()> {
+        let payload = serde_json::to_vec(task)?;
+        self.jetstream.publish(REDUCE_SUBJECT, payload.into()).await?.await?;
+        Ok(())
+    }
+
+    /// Stages `pairs` for `job_id`'s `partition`, produced by map task
+    /// `task_id`, in the object store, chunked into ~128 KiB segments, and
+    /// returns the key reduce tasks should reference instead of carrying the
+    /// pairs inline. Keyed per task (not just per partition) so concurrent
+    /// map tasks for the same job never overwrite each other's output.
+    pub async fn stage_partition(&self, job_id: &str, partition: usize, task_id: &str, pairs: &[KeyValuePair]) -> Result<String> {
+        let key = format!("{job_id}/partition-{partition}/{task_id}");
+        let body = serde_json::to_vec(pairs)?;
+
+        // `put` uploads in ~128 KiB chunks internally, so a large partition
+        // never has to be held whole on the wire.
+        let mut store = self.object_store.clone();
+        store.put(key.as_str(), &mut body.as_slice()).await?;
+        Ok(key)
+    }
+
+    pub async fn load_partition(&self, key: &str) -> Result<Vec<KeyValuePair>> {
+        let mut object = self.object_store.get(key).await?;
+        let mut body = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut object, &mut body).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Loads whichever of `keys` actually exist, skipping any that were
+    /// never staged because the map task they came from didn't produce a
+    /// key for this partition.
+    pub async fn load_existing_partitions(&self, keys: &[String]) -> Result<Vec<KeyValuePair>> {
+        let mut pairs = Vec::new();
+        for key in keys {
+            match self.load_partition(key).await {
+                Ok(mut loaded) => pairs.append(&mut loaded),
+                Err(_) => continue,
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Subscribes to `job_id`'s map-completion subject. Must be called
+    /// before the corresponding map tasks are published, or an early
+    /// completion signal could be missed.
+    pub async fn subscribe_map_done(&self, job_id: &str) -> Result<async_nats::Subscriber> {
+        Ok(self.client.subscribe(map_done_subject(job_id)).await?)
+    }
+
+    /// Signals that map task `task_id` finished staging its output.
+    pub async fn publish_map_done(&self, job_id: &str, task_id: &str) -> Result<()> {
+        self.client.publish(map_done_subject(job_id), task_id.as_bytes().to_vec().into()).await?;
+        Ok(())
+    }
+
+    /// Subscribes to `job_id`'s reduce-completion subject. Must be called
+    /// before the corresponding reduce tasks are published, for the same
+    /// reason as [`Self::subscribe_map_done`].
+    pub async fn subscribe_reduce_done(&self, job_id: &str) -> Result<async_nats::Subscriber> {
+        Ok(self.client.subscribe(reduce_done_subject(job_id)).await?)
+    }
+
+    /// Signals that `partition`'s reduce finished, carrying its result
+    /// inline rather than staging it, since a single reduced string is
+    /// cheap enough to put directly on the wire.
+    pub async fn publish_reduce_done(&self, job_id: &str, partition: usize, result: &str) -> Result<()> {
+        let payload = serde_json::to_vec(&ReduceDone { partition, result: result.to_string() })?;
+        self.client.publish(reduce_done_subject(job_id), payload.into()).await?;
+        Ok(())
+    }
+
+    /// Pull-consumes up to `batch` map tasks, acking each only after
+    /// `handler` returns `Ok`, so a task that isn't acked within
+    /// [`ACK_WAIT`] is redelivered to another worker.
+    pub async fn consume_map_tasks<F, Fut>(&self, batch: usize, handler: F) -> Result<()>
+    where
+        F: Fn(MapTask) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let stream = self.jetstream.get_stream(MAP_STREAM).await?;
+        let consumer = stream
+            .get_or_create_consumer(
+                "mr-map-workers",
+                async_nats::jetstream::consumer::pull::Config {
+                    durable_name: Some("mr-map-workers".to_string()),
+                    ack_policy: async_nats::jetstream::consumer::AckPolicy::Explicit,
+                    ack_wait: ACK_WAIT,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let mut messages = consumer.fetch().max_messages(batch).messages().await?;
+        while let Some(message) = futures_util::TryStreamExt::try_next(&mut messages).await? {
+            let task: MapTask = serde_json::from_slice(&message.payload)?;
+            match handler(task).await {
+                Ok(()) => message.ack().await.map_err(|e| format!("ack failed: {e}"))?,
+                Err(e) => eprintln!("map task failed, leaving for redelivery: {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Mirror of [`Self::consume_map_tasks`] for the reduce work queue.
+    pub async fn consume_reduce_tasks<F, Fut>(&self, batch: usize, handler: F) -> Result<()>
+    where
+        F: Fn(ReduceTask) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let stream = self.jetstream.get_stream(REDUCE_STREAM).await?;
+        let consumer = stream
+            .get_or_create_consumer(
+                "mr-reduce-workers",
+                async_nats::jetstream::consumer::pull::Config {
+                    durable_name: Some("mr-reduce-workers".to_string()),
+                    ack_policy: async_nats::jetstream::consumer::AckPolicy::Explicit,
+                    ack_wait: ACK_WAIT,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let mut messages = consumer.fetch().max_messages(batch).messages().await?;
+        while let Some(message) = futures_util::TryStreamExt::try_next(&mut messages).await? {
+            let task: ReduceTask = serde_json::from_slice(&message.payload)?;
+            match handler(task).await {
+                Ok(()) => message.ack().await.map_err(|e| format!("ack failed: {e}"))?,
+                Err(e) => eprintln!("reduce task failed, leaving for redelivery: {e}"),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
+pub mod background_worker;
 pub mod coordinator;
 pub mod client;
 pub mod server;
 pub mod functions;
+pub mod chunking;
+pub mod fluentd;
+pub mod input_source;
+pub mod jetstream_transport;
+pub mod metrics;
+pub mod scheduler;
+pub mod shuffle;
+pub mod storage;
+pub mod tranquilizer;
 pub mod utils;
 pub mod config;
 
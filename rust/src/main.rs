@@ -1,6 +1,7 @@
 use chrono::Local;
 use colored::Colorize;
 use distributed_mapreduce::client;
+use distributed_mapreduce::config::Config;
 use distributed_mapreduce::server;
 use std::fs::File;
 use std::io::Write;
@@ -12,6 +13,11 @@ type Result<T> = std::result::Result<T, Error>;
 const DEFAULT_MAP_FN: &str = "word_count";
 const DEFAULT_REDUCE_FN: &str = "sum";
 
+// Defaults for the `schedule` subcommand, mirroring a recurring hourly job
+// that retries a few times before giving up on a given run.
+const DEFAULT_SCHEDULE_INTERVAL_SECS: u64 = 3600;
+const DEFAULT_SCHEDULE_MAX_RETRIES: u32 = 3;
+
 fn save_results(
     result: &str,
     input_files: &[String],
@@ -130,8 +136,18 @@ fn print_results_graph(result: &str, output_file: &str) {
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    // Built by hand rather than `#[tokio::main]` so `blocking_pool_size` can
+    // size the pool that map/reduce invocations run on.
+    let blocking_pool_size = Config::new().blocking_pool_size;
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .max_blocking_threads(blocking_pool_size)
+        .build()?
+        .block_on(run())
+}
+
+async fn run() -> Result<()> {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 2 {
@@ -141,12 +157,25 @@ async fn main() -> Result<()> {
             "  Client mode: {} client <directory> [server_address]",
             args[0]
         );
+        eprintln!(
+            "  Schedule mode: {} schedule <directory> [server_address] [interval_secs]",
+            args[0]
+        );
+        eprintln!(
+            "  Worker mode: {} worker <listen_address> <primary_address>",
+            args[0]
+        );
         eprintln!();
         eprintln!("{}", "Arguments:".yellow());
         eprintln!("  server          Start in server mode");
         eprintln!("  client          Start in client mode");
+        eprintln!("  schedule        Register a recurring job on the server");
+        eprintln!("  worker          Register as a worker with a primary server and serve map/reduce RPCs");
         eprintln!("  directory       Directory containing txt files to process");
         eprintln!("  server_address  Optional server address (default: http://localhost:50051)");
+        eprintln!("  interval_secs   Optional recurrence interval for schedule mode (default: {})", DEFAULT_SCHEDULE_INTERVAL_SECS);
+        eprintln!("  listen_address  Address this worker listens on, e.g. 0.0.0.0:50052");
+        eprintln!("  primary_address Address of the primary server to register with, e.g. http://localhost:50051");
         std::process::exit(1);
     }
 
@@ -201,8 +230,61 @@ async fn main() -> Result<()> {
             );
             println!("Total time: {}", format!("{:.2?}", elapsed_time).cyan());
         }
+        "schedule" => {
+            if args.len() < 3 {
+                eprintln!("{}", "Error: directory path required for schedule mode".red());
+                std::process::exit(1);
+            }
+
+            let directory = &args[2];
+            let server_addr = if args.len() > 3 {
+                args[3].clone()
+            } else {
+                "http://localhost:50051".to_string()
+            };
+            let interval_secs = args
+                .get(4)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_SCHEDULE_INTERVAL_SECS);
+
+            let txt_files = client::get_txt_files(directory).await?;
+
+            if txt_files.is_empty() {
+                println!("{}", "No .txt files found in directory".yellow());
+                return Ok(());
+            }
+
+            print_job_info(&txt_files, &server_addr, DEFAULT_MAP_FN, DEFAULT_REDUCE_FN);
+
+            let schedule_id = client::schedule_job(
+                server_addr,
+                txt_files,
+                DEFAULT_MAP_FN.to_string(),
+                DEFAULT_REDUCE_FN.to_string(),
+                interval_secs,
+                DEFAULT_SCHEDULE_MAX_RETRIES,
+                "skip".to_string(),
+            )
+            .await?;
+
+            println!("\n{}", "Recurring Job Scheduled!".green());
+            println!("Schedule ID: {}", schedule_id.yellow());
+            println!("Runs every {} seconds", interval_secs.to_string().yellow());
+        }
+        "worker" => {
+            if args.len() < 4 {
+                eprintln!("{}", "Error: listen_address and primary_address required for worker mode".red());
+                std::process::exit(1);
+            }
+
+            let listen_addr = args[2].clone();
+            let primary_addr = args[3].clone();
+
+            println!("\n{}", "Starting Map-Reduce Worker...".blue());
+            client::run_worker(primary_addr, listen_addr).await?;
+        }
         _ => {
-            eprintln!("{}", "Invalid mode. Use 'server' or 'client'".red());
+            eprintln!("{}", "Invalid mode. Use 'server', 'client', 'schedule', or 'worker'".red());
             std::process::exit(1);
         }
     }
@@ -0,0 +1,127 @@
+//! Prometheus metrics for job throughput, RPC latency, and worker health,
+//! served in text exposition format over a small HTTP endpoint alongside the
+//! tonic gRPC server.
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+pub struct Metrics {
+    registry: Registry,
+    rpcs_total: IntCounterVec,
+    rpc_latency_seconds: HistogramVec,
+    bytes_processed_total: IntCounterVec,
+    records_processed_total: IntCounterVec,
+    reduce_failures_total: IntCounterVec,
+    workers_registered: IntGauge,
+    workers_healthy: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        let registry = Registry::new();
+
+        let rpcs_total = IntCounterVec::new(
+            Opts::new("mapreduce_rpcs_total", "RPCs served, by method and function name"),
+            &["rpc", "function"],
+        )
+        .unwrap();
+        let rpc_latency_seconds = HistogramVec::new(
+            HistogramOpts::new("mapreduce_rpc_latency_seconds", "RPC latency in seconds"),
+            &["rpc", "function"],
+        )
+        .unwrap();
+        let bytes_processed_total = IntCounterVec::new(
+            Opts::new("mapreduce_bytes_processed_total", "Bytes processed, by RPC"),
+            &["rpc"],
+        )
+        .unwrap();
+        let records_processed_total = IntCounterVec::new(
+            Opts::new("mapreduce_records_processed_total", "Records (key-value pairs) processed, by RPC"),
+            &["rpc"],
+        )
+        .unwrap();
+        let reduce_failures_total = IntCounterVec::new(
+            Opts::new("mapreduce_reduce_failures_total", "Reduce RPC failures, by reason"),
+            &["reason"],
+        )
+        .unwrap();
+        let workers_registered = IntGauge::new("mapreduce_workers_registered", "Currently registered workers").unwrap();
+        let workers_healthy = IntGauge::new("mapreduce_workers_healthy", "Workers that have sent a heartbeat within the timeout").unwrap();
+
+        registry.register(Box::new(rpcs_total.clone())).unwrap();
+        registry.register(Box::new(rpc_latency_seconds.clone())).unwrap();
+        registry.register(Box::new(bytes_processed_total.clone())).unwrap();
+        registry.register(Box::new(records_processed_total.clone())).unwrap();
+        registry.register(Box::new(reduce_failures_total.clone())).unwrap();
+        registry.register(Box::new(workers_registered.clone())).unwrap();
+        registry.register(Box::new(workers_healthy.clone())).unwrap();
+
+        Arc::new(Self {
+            registry,
+            rpcs_total,
+            rpc_latency_seconds,
+            bytes_processed_total,
+            records_processed_total,
+            reduce_failures_total,
+            workers_registered,
+            workers_healthy,
+        })
+    }
+
+    pub fn observe_rpc(&self, rpc: &str, function: &str, elapsed: Duration, bytes: u64, records: u64) {
+        self.rpcs_total.with_label_values(&[rpc, function]).inc();
+        self.rpc_latency_seconds
+            .with_label_values(&[rpc, function])
+            .observe(elapsed.as_secs_f64());
+        self.bytes_processed_total.with_label_values(&[rpc]).inc_by(bytes);
+        self.records_processed_total.with_label_values(&[rpc]).inc_by(records);
+    }
+
+    pub fn record_reduce_failure(&self, reason: &str) {
+        self.reduce_failures_total.with_label_values(&[reason]).inc();
+    }
+
+    pub fn set_worker_counts(&self, registered: i64, healthy: i64) {
+        self.workers_registered.set(registered);
+        self.workers_healthy.set(healthy);
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let metric_families = self.registry.gather();
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(&metric_families, &mut buf).unwrap();
+        buf
+    }
+}
+
+/// Serves `metrics` as Prometheus text exposition format on `GET /metrics`.
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) {
+    let make_svc = make_service_fn(move |_conn| {
+        let metrics = metrics.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let metrics = metrics.clone();
+                async move { Ok::<_, Infallible>(handle(req, metrics)) }
+            }))
+        }
+    });
+
+    if let Err(e) = Server::bind(&addr).serve(make_svc).await {
+        eprintln!("metrics server error: {}", e);
+    }
+}
+
+fn handle(req: Request<Body>, metrics: Arc<Metrics>) -> Response<Body> {
+    if req.uri().path() == "/metrics" {
+        Response::new(Body::from(metrics.encode()))
+    } else {
+        let mut resp = Response::new(Body::from("not found"));
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        resp
+    }
+}
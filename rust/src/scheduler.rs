@@ -0,0 +1,311 @@
+//! Recurring map-reduce jobs: schedule entries fire on a fixed interval,
+//! submitting through the same [`client::run_map_reduce_job`] path an
+//! on-demand job uses. Driven by the generic [`BackgroundWorker`]
+//! abstraction rather than a bespoke loop, so its lifecycle is introspectable
+//! the same way any other background task in this crate is.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use uuid::Uuid;
+
+use crate::background_worker::{BackgroundWorker, WorkerHandle, WorkerState, spawn_background_worker};
+use crate::client;
+
+/// How often the tick loop checks for due entries. Independent of any
+/// entry's own `interval`.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to do with a tick that comes due while the previous run of the same
+/// entry is still in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Drop this tick; the entry stays on its regular cadence.
+    Skip,
+    /// Run once the in-flight run finishes, instead of dropping the tick.
+    Queue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Success,
+    Failed,
+}
+
+/// A job spec to register with [`Scheduler::register`].
+pub struct ScheduleSpec {
+    pub server_addr: String,
+    pub files: Vec<String>,
+    pub map_function: String,
+    pub reduce_function: String,
+    pub interval: Duration,
+    pub max_retries: u32,
+    pub overlap_policy: OverlapPolicy,
+}
+
+/// One registered recurring job: its spec plus bookkeeping of the last run
+/// and when it's next due.
+#[derive(Debug, Clone)]
+pub struct ScheduleEntry {
+    pub id: String,
+    pub server_addr: String,
+    pub files: Vec<String>,
+    pub map_function: String,
+    pub reduce_function: String,
+    pub interval: Duration,
+    pub max_retries: u32,
+    pub overlap_policy: OverlapPolicy,
+    pub next_run: SystemTime,
+    pub last_run_status: Option<RunStatus>,
+    pub paused: bool,
+    /// Whether a run of this entry is currently in flight.
+    running: bool,
+    /// Set under [`OverlapPolicy::Queue`] when a tick came due mid-run; the
+    /// run that's in flight triggers the queued one immediately on finishing
+    /// instead of waiting for `interval` to elapse again.
+    queued: bool,
+}
+
+/// A table of recurring job entries plus the tick loop that fires them.
+pub struct Scheduler {
+    entries: Arc<Mutex<HashMap<String, ScheduleEntry>>>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self { entries: Arc::new(Mutex::new(HashMap::new())) }
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, spec: ScheduleSpec) -> String {
+        let id = Uuid::new_v4().to_string();
+        let entry = ScheduleEntry {
+            id: id.clone(),
+            server_addr: spec.server_addr,
+            files: spec.files,
+            map_function: spec.map_function,
+            reduce_function: spec.reduce_function,
+            interval: spec.interval,
+            max_retries: spec.max_retries,
+            overlap_policy: spec.overlap_policy,
+            next_run: SystemTime::now() + spec.interval,
+            last_run_status: None,
+            paused: false,
+            running: false,
+            queued: false,
+        };
+        self.entries.lock().unwrap().insert(id.clone(), entry);
+        id
+    }
+
+    pub fn pause(&self, id: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(id) {
+            entry.paused = true;
+        }
+    }
+
+    pub fn resume(&self, id: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(id) {
+            entry.paused = false;
+        }
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.entries.lock().unwrap().remove(id);
+    }
+
+    pub fn list(&self) -> Vec<ScheduleEntry> {
+        self.entries.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Spawns the tick loop driving this scheduler, returning a handle the
+    /// caller can poll via [`WorkerHandle::status`].
+    pub fn spawn(self: Arc<Self>) -> WorkerHandle {
+        spawn_background_worker(SchedulerTick { scheduler: self })
+    }
+}
+
+/// Runs one entry to completion (with retries up to `max_retries`) and
+/// records the outcome, started off the tick loop so a slow run never
+/// delays the next tick.
+fn spawn_run(scheduler: Arc<Scheduler>, id: String) {
+    tokio::spawn(async move {
+        let (server_addr, files, map_function, reduce_function, max_retries) = {
+            let entries = scheduler.entries.lock().unwrap();
+            match entries.get(&id) {
+                Some(entry) => (
+                    entry.server_addr.clone(),
+                    entry.files.clone(),
+                    entry.map_function.clone(),
+                    entry.reduce_function.clone(),
+                    entry.max_retries,
+                ),
+                None => return,
+            }
+        };
+
+        let mut attempt = 0;
+        let status = loop {
+            match client::run_map_reduce_job(
+                server_addr.clone(),
+                files.clone(),
+                map_function.clone(),
+                reduce_function.clone(),
+            )
+            .await
+            {
+                Ok(_) => break RunStatus::Success,
+                Err(e) if attempt < max_retries => {
+                    attempt += 1;
+                    eprintln!("scheduled job '{id}' failed (attempt {attempt}/{max_retries}): {e}");
+                }
+                Err(e) => {
+                    eprintln!("scheduled job '{id}' exhausted retries: {e}");
+                    break RunStatus::Failed;
+                }
+            }
+        };
+
+        let mut entries = scheduler.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(&id) {
+            entry.last_run_status = Some(status);
+            entry.running = false;
+            if entry.queued {
+                // A tick came due mid-run under `OverlapPolicy::Queue`; run
+                // again right away instead of waiting for `interval`.
+                entry.queued = false;
+                entry.next_run = SystemTime::now();
+            } else {
+                entry.next_run = SystemTime::now() + entry.interval;
+            }
+        }
+    });
+}
+
+struct SchedulerTick {
+    scheduler: Arc<Scheduler>,
+}
+
+#[async_trait::async_trait]
+impl BackgroundWorker for SchedulerTick {
+    async fn work(&mut self) -> Result<WorkerState, Box<dyn std::error::Error + Send + Sync>> {
+        let now = SystemTime::now();
+
+        let due: Vec<String> = {
+            let mut entries = self.scheduler.entries.lock().unwrap();
+            entries
+                .values_mut()
+                .filter(|entry| !entry.paused && entry.next_run <= now)
+                .filter_map(|entry| {
+                    if entry.running {
+                        match entry.overlap_policy {
+                            OverlapPolicy::Skip => {
+                                entry.next_run = now + entry.interval;
+                                None
+                            }
+                            OverlapPolicy::Queue => {
+                                entry.queued = true;
+                                None
+                            }
+                        }
+                    } else {
+                        entry.running = true;
+                        Some(entry.id.clone())
+                    }
+                })
+                .collect()
+        };
+
+        for id in due {
+            spawn_run(Arc::clone(&self.scheduler), id);
+        }
+
+        Ok(WorkerState::Idle { wait_until: now + TICK_INTERVAL })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A spec whose `server_addr` nothing listens on, so `run_map_reduce_job`
+    /// fails fast with a connection error instead of actually doing work.
+    fn unreachable_spec(overlap_policy: OverlapPolicy) -> ScheduleSpec {
+        ScheduleSpec {
+            server_addr: "http://127.0.0.1:1".to_string(),
+            files: vec!["test.txt".to_string()],
+            map_function: "word_count".to_string(),
+            reduce_function: "sum".to_string(),
+            interval: Duration::from_secs(60),
+            max_retries: 0,
+            overlap_policy,
+        }
+    }
+
+    #[tokio::test]
+    async fn skip_policy_drops_a_due_tick_while_the_previous_run_is_in_flight() {
+        let scheduler = Arc::new(Scheduler::new());
+        let id = scheduler.register(unreachable_spec(OverlapPolicy::Skip));
+        {
+            let mut entries = scheduler.entries.lock().unwrap();
+            let entry = entries.get_mut(&id).unwrap();
+            entry.running = true;
+            entry.next_run = SystemTime::now();
+        }
+
+        SchedulerTick { scheduler: Arc::clone(&scheduler) }.work().await.unwrap();
+
+        let entries = scheduler.entries.lock().unwrap();
+        let entry = entries.get(&id).unwrap();
+        assert!(entry.running, "Skip must not touch the in-flight run");
+        assert!(!entry.queued, "Skip drops the tick rather than remembering it");
+        assert!(entry.next_run > SystemTime::now(), "Skip should push the tick to the next interval");
+    }
+
+    #[tokio::test]
+    async fn queue_policy_remembers_a_due_tick_while_the_previous_run_is_in_flight() {
+        let scheduler = Arc::new(Scheduler::new());
+        let id = scheduler.register(unreachable_spec(OverlapPolicy::Queue));
+        {
+            let mut entries = scheduler.entries.lock().unwrap();
+            let entry = entries.get_mut(&id).unwrap();
+            entry.running = true;
+            entry.next_run = SystemTime::now();
+        }
+
+        SchedulerTick { scheduler: Arc::clone(&scheduler) }.work().await.unwrap();
+
+        let entries = scheduler.entries.lock().unwrap();
+        let entry = entries.get(&id).unwrap();
+        assert!(entry.running, "Queue must not touch the in-flight run either");
+        assert!(entry.queued, "Queue should remember the tick instead of dropping it");
+    }
+
+    #[tokio::test]
+    async fn exhausting_retries_marks_the_entry_failed_and_reschedules() {
+        let scheduler = Arc::new(Scheduler::new());
+        let mut spec = unreachable_spec(OverlapPolicy::Skip);
+        spec.max_retries = 2;
+        let id = scheduler.register(spec);
+
+        spawn_run(Arc::clone(&scheduler), id.clone());
+
+        for _ in 0..50 {
+            if scheduler.entries.lock().unwrap().get(&id).unwrap().last_run_status.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let entries = scheduler.entries.lock().unwrap();
+        let entry = entries.get(&id).unwrap();
+        assert_eq!(entry.last_run_status, Some(RunStatus::Failed));
+        assert!(!entry.running, "a finished run frees the entry back up");
+        assert!(entry.next_run > SystemTime::now(), "a failed run still reschedules for the next interval");
+    }
+}
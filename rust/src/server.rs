@@ -1,36 +1,116 @@
 use tonic::{Request, Response, Status, transport::Server};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::AsyncBufReadExt;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::config::Config;
-use crate::coordinator::Coordinator;
+use crate::background_worker::{self, BackgroundWorker, WorkerState};
+use crate::config::{Config, S3Config};
+use crate::coordinator::{Coordinator, JobControl, TaskStatus};
+use crate::tranquilizer::Tranquilizer;
+use crate::fluentd::{self, FluentdSink};
+use crate::functions::FunctionRegistry;
+use crate::input_source;
+use crate::jetstream_transport::{JetStreamTransport, MapTask, ReduceTask};
+use crate::metrics::Metrics;
+use crate::scheduler::{OverlapPolicy, ScheduleSpec};
 use crate::mapreduce::map_reduce_service_server::{MapReduceService as MapReduceServiceTrait, MapReduceServiceServer};
 use crate::mapreduce::{
-    MapRequest, 
-    MapResponse, 
-    ReduceRequest, 
+    MapRequest,
+    MapResponse,
+    ReduceRequest,
     ReduceResponse,
     PingRequest,
     PingResponse,
-    KeyValuePair
+    KeyValuePair,
+    ListWorkersRequest,
+    ListWorkersResponse,
+    WorkerInfo as WorkerInfoProto,
+    PauseJobRequest,
+    PauseJobResponse,
+    ResumeJobRequest,
+    ResumeJobResponse,
+    CancelJobRequest,
+    CancelJobResponse,
+    SetTranquilityRequest,
+    SetTranquilityResponse,
+    ScheduleJobRequest,
+    ScheduleJobResponse,
+    RegisterWorkerRequest,
+    RegisterWorkerResponse,
 };
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Default)]
 pub struct MapReduceService {
     coordinator: Arc<Coordinator>,
+    registry: Arc<FunctionRegistry>,
+    config: Config,
+    metrics: Arc<Metrics>,
+    fluentd: FluentdSink,
+    /// This process's own id in `coordinator`'s worker registry. The
+    /// `map`/`reduce` RPCs below run in this very process rather than
+    /// dispatching to a separate remote worker, so the server registers
+    /// itself as its own sole worker — otherwise `Coordinator`'s worker
+    /// bookkeeping (and `list_workers`) would never reflect anything real.
+    worker_id: String,
+    /// Shared across every `map` RPC, mirroring how the JetStream consumer
+    /// loop keeps one `Tranquilizer` per task kind rather than one per task,
+    /// so its moving average reflects this worker's actual recent pace.
+    map_tranquilizer: Arc<tokio::sync::Mutex<Tranquilizer>>,
+    reduce_tranquilizer: Arc<tokio::sync::Mutex<Tranquilizer>>,
+}
+
+impl Default for MapReduceService {
+    fn default() -> Self {
+        let coordinator = Arc::new(Coordinator::default());
+        let worker_id = coordinator.register_worker("self".to_string());
+        Self {
+            coordinator,
+            registry: Arc::new(FunctionRegistry::new()),
+            config: Config::default(),
+            metrics: Metrics::new(),
+            fluentd: FluentdSink::from_config(&Config::default()),
+            worker_id,
+            map_tranquilizer: Arc::new(tokio::sync::Mutex::new(Tranquilizer::new())),
+            reduce_tranquilizer: Arc::new(tokio::sync::Mutex::new(Tranquilizer::new())),
+        }
+    }
 }
 
 impl MapReduceService {
     pub fn new() -> Self {
+        Self::new_with_registry(FunctionRegistry::new())
+    }
+
+    /// Like [`Self::new`], but dispatches `map`/`reduce` through `registry`
+    /// instead of only the built-in `word_count`/`sum` functions, so a
+    /// caller can register its own `MapFunction`/`ReduceFunction`
+    /// implementations for the server to actually run by name (see
+    /// `examples/custom_functions.rs`).
+    pub fn new_with_registry(registry: FunctionRegistry) -> Self {
         let config = Config::new();
         println!("Starting MapReduce service with {} worker threads", config.worker_threads);
-        
+
+        let task_store = crate::storage::from_config(&config).unwrap_or_else(|e| {
+            eprintln!("failed to open task store, falling back to in-memory only: {e}");
+            Arc::new(crate::storage::NullTaskStore)
+        });
+        let fluentd = FluentdSink::from_config(&config);
+        let coordinator = Arc::new(
+            Coordinator::new_with_store(config.worker_threads, task_store).with_fluentd(fluentd.clone()),
+        );
+        let worker_id = coordinator.register_worker("self".to_string());
+
         Self {
-            coordinator: Arc::new(Coordinator::new_with_threads(config.worker_threads)),
+            coordinator,
+            registry: Arc::new(registry),
+            config,
+            metrics: Metrics::new(),
+            fluentd,
+            worker_id,
+            map_tranquilizer: Arc::new(tokio::sync::Mutex::new(Tranquilizer::new())),
+            reduce_tranquilizer: Arc::new(tokio::sync::Mutex::new(Tranquilizer::new())),
         }
     }
 }
@@ -39,61 +119,178 @@ impl MapReduceService {
 impl MapReduceServiceTrait for MapReduceService {
     async fn map(&self, request: Request<MapRequest>) -> std::result::Result<Response<MapResponse>, Status> {
         let req = request.into_inner();
-        
-        // Read file content in chunks
-        let file = match tokio::fs::File::open(&req.file_path).await {
-            Ok(file) => file,
-            Err(e) => return Err(Status::not_found(e.to_string())),
+        let started = std::time::Instant::now();
+
+        // Blocks while the job is paused and bails out entirely if it's been
+        // cancelled, mirroring how the JetStream consumer loop already
+        // respects job control between task units.
+        if await_job_control(&self.coordinator, &req.job_id).await {
+            return Err(Status::cancelled(format!("job '{}' was cancelled", req.job_id)));
+        }
+
+        if self.registry.get_map_function(&req.map_function).is_none() {
+            return Err(Status::not_found(format!("unknown map function '{}'", req.map_function)));
+        }
+
+        // Track this RPC as a real task on the coordinator, so pause/cancel
+        // and the worker registry (otherwise only ever exercised by tests)
+        // reflect what the server is actually doing.
+        let task_id = self.coordinator.create_task(&req.job_id);
+        self.coordinator.assign_task(task_id.clone(), self.worker_id.clone());
+
+        // Stream just this request's slice of the input (the whole file when
+        // `length` is 0) regardless of whether it lives on local disk or in
+        // an object store, so a single worker never has to buffer more than
+        // its assigned chunk.
+        let source = input_source::resolve(&req.file_path, &self.config.s3);
+        let length = if req.length > 0 { Some(req.length) } else { None };
+        let mut reader = match source.open_range(&req.file_path, req.offset, length).await {
+            Ok(reader) => reader,
+            Err(e) => {
+                self.coordinator.update_task_status(task_id, TaskStatus::Failed);
+                return Err(Status::not_found(e.to_string()));
+            }
         };
 
-        let mut reader = BufReader::new(file);
         let mut buffer = String::new();
-        let mut word_counts: HashMap<String, i32> = HashMap::new();
+        let mut lines = Vec::new();
+        let mut bytes_read: u64 = 0;
 
         while let Ok(n) = reader.read_line(&mut buffer).await {
             if n == 0 { break; }
-            
-            // Process the line
-            for word in buffer.split_whitespace() {
-                *word_counts.entry(word.to_string()).or_insert(0) += 1;
-            }
-            buffer.clear();
+            bytes_read += n as u64;
+            lines.push(std::mem::take(&mut buffer));
         }
 
-        // Convert to KeyValuePair
-        let intermediate_results: Vec<KeyValuePair> = word_counts
-            .into_iter()
-            .map(|(key, value)| KeyValuePair {
-                key,
-                value: value.to_string(),
-            })
-            .collect();
+        // The actual mapping is CPU-bound, so it runs on the blocking pool
+        // rather than the async runtime's own worker threads, keeping the
+        // heartbeat and health-check loops responsive under large inputs.
+        let registry = Arc::clone(&self.registry);
+        let map_function = req.map_function.clone();
+        let in_flight = self.map_tranquilizer.lock().await.begin();
+        let intermediate_results = match tokio::task::spawn_blocking(move || {
+            let map_fn = registry.get_map_function(&map_function).expect("checked before spawning");
+            lines
+                .iter()
+                .flat_map(|line| map_fn.map(line))
+                .map(|(key, value)| KeyValuePair { key, value })
+                .collect::<Vec<_>>()
+        })
+        .await
+        {
+            Ok(results) => results,
+            Err(e) => {
+                self.coordinator.update_task_status(task_id, TaskStatus::Failed);
+                return Err(Status::internal(format!("map task panicked: {}", e)));
+            }
+        };
+
+        // Throttles this worker to the job's configured `tranquility` (see
+        // `set_tranquility`), the same adaptive pacing the JetStream consumer
+        // loop applies to its own map tasks.
+        let tranquility = self.coordinator.watch_job(&req.job_id).borrow().tranquility;
+        self.map_tranquilizer.lock().await.tranquilize(in_flight, tranquility).await;
+
+        self.coordinator.update_task_status(task_id, TaskStatus::Completed);
+
+        self.metrics.observe_rpc(
+            "map",
+            &req.map_function,
+            started.elapsed(),
+            bytes_read,
+            intermediate_results.len() as u64,
+        );
+
+        self.fluentd
+            .emit(
+                "map.done",
+                fluentd::fields(&[
+                    ("job_id", &req.job_id),
+                    ("file_path", &req.file_path),
+                    ("record_count", &intermediate_results.len().to_string()),
+                    ("elapsed_ms", &started.elapsed().as_millis().to_string()),
+                ]),
+            )
+            .await;
 
-        Ok(Response::new(MapResponse { 
-            intermediate_results 
+        Ok(Response::new(MapResponse {
+            intermediate_results
         }))
     }
 
     async fn reduce(&self, request: Request<ReduceRequest>) -> std::result::Result<Response<ReduceResponse>, Status> {
         let req = request.into_inner();
-        
-        let mut results: HashMap<String, i32> = HashMap::new();
-        
-        // Group by key and sum values
-        for kv in req.intermediate_results {
-            let value = match kv.value.parse::<i32>() {
-                Ok(v) => v,
-                Err(_) => return Err(Status::invalid_argument("Invalid value")),
-            };
-            *results.entry(kv.key).or_insert(0) += value;
+        let started = std::time::Instant::now();
+
+        if await_job_control(&self.coordinator, &req.job_id).await {
+            return Err(Status::cancelled(format!("job '{}' was cancelled", req.job_id)));
         }
 
-        // Convert final results to string
-        let final_result = results
-            .into_iter()
-            .map(|(k, v)| format!("{}:{}", k, v))
-            .collect::<Vec<_>>()
-            .join(", ");
+        if self.registry.get_reduce_function(&req.reduce_function).is_none() {
+            self.metrics.record_reduce_failure("unknown_function");
+            return Err(Status::not_found(format!("unknown reduce function '{}'", req.reduce_function)));
+        }
+
+        let task_id = self.coordinator.create_task(&req.job_id);
+        self.coordinator.assign_task(task_id.clone(), self.worker_id.clone());
+
+        let record_count = req.intermediate_results.len() as u64;
+        let bytes: u64 = req
+            .intermediate_results
+            .iter()
+            .map(|kv| (kv.key.len() + kv.value.len()) as u64)
+            .sum();
+
+        // Grouping and reducing are CPU-bound, so they run on the blocking
+        // pool rather than the async runtime's own worker threads, keeping
+        // the heartbeat and health-check loops responsive under large inputs.
+        let registry = Arc::clone(&self.registry);
+        let reduce_function = req.reduce_function.clone();
+        let intermediate_results = req.intermediate_results;
+        let in_flight = self.reduce_tranquilizer.lock().await.begin();
+        let final_result = match tokio::task::spawn_blocking(move || {
+            let reduce_fn = registry.get_reduce_function(&reduce_function).expect("checked before spawning");
+
+            let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+            for kv in intermediate_results {
+                grouped.entry(kv.key).or_default().push(kv.value);
+            }
+
+            grouped
+                .into_iter()
+                .map(|(key, values)| {
+                    let reduced = reduce_fn.reduce(&key, values);
+                    format!("{}:{}", key, reduced)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                self.coordinator.update_task_status(task_id, TaskStatus::Failed);
+                return Err(Status::internal(format!("reduce task panicked: {}", e)));
+            }
+        };
+
+        let tranquility = self.coordinator.watch_job(&req.job_id).borrow().tranquility;
+        self.reduce_tranquilizer.lock().await.tranquilize(in_flight, tranquility).await;
+
+        self.coordinator.update_task_status(task_id, TaskStatus::Completed);
+
+        self.metrics.observe_rpc("reduce", &req.reduce_function, started.elapsed(), bytes, record_count);
+
+        self.fluentd
+            .emit(
+                "reduce.done",
+                fluentd::fields(&[
+                    ("job_id", &req.job_id),
+                    ("record_count", &record_count.to_string()),
+                    ("elapsed_ms", &started.elapsed().as_millis().to_string()),
+                ]),
+            )
+            .await;
 
         Ok(Response::new(ReduceResponse {
             final_result
@@ -109,34 +306,163 @@ impl MapReduceServiceTrait for MapReduceService {
             None => "",
         };
 
+        // A ping from a worker id this coordinator actually registered
+        // refreshes its liveness the same way the self-registered worker's
+        // own heartbeat does, so a remote worker pinging in between tasks
+        // doesn't get marked `Done` by `check_worker_health`. An id nobody
+        // registered reports UNKNOWN rather than silently being accepted.
         let status = if worker_id.is_empty() {
-            "UNKNOWN"
+            "UNKNOWN".to_string()
+        } else if self.coordinator.heartbeat(worker_id.to_string()) {
+            "OK".to_string()
         } else {
-            // Always return OK for valid worker IDs in test mode
-            "OK"
-        }.to_string();
+            "UNKNOWN".to_string()
+        };
 
         Ok(Response::new(PingResponse { status }))
     }
+
+    /// Registers a remote worker at `address` so it shows up in
+    /// `list_workers` and `check_worker_health` tracks its liveness, the
+    /// RPC a worker process calls on startup instead of only this server's
+    /// own in-process `"self"` entry ever existing.
+    async fn register_worker(&self, request: Request<RegisterWorkerRequest>) -> std::result::Result<Response<RegisterWorkerResponse>, Status> {
+        let address = request.into_inner().address;
+        if address.is_empty() {
+            return Err(Status::invalid_argument("address is required"));
+        }
+        let worker_id = self.coordinator.register_worker(address);
+        Ok(Response::new(RegisterWorkerResponse { worker_id }))
+    }
+
+    async fn list_workers(&self, _request: Request<ListWorkersRequest>) -> std::result::Result<Response<ListWorkersResponse>, Status> {
+        let workers = self
+            .coordinator
+            .list_workers()
+            .into_iter()
+            .map(|w| WorkerInfoProto {
+                id: w.id,
+                address: w.address,
+                status: match w.state {
+                    WorkerState::Busy => "active".to_string(),
+                    WorkerState::Idle { .. } => "idle".to_string(),
+                    WorkerState::Done => "dead".to_string(),
+                },
+            })
+            .collect();
+
+        Ok(Response::new(ListWorkersResponse { workers }))
+    }
+
+    async fn pause_job(&self, request: Request<PauseJobRequest>) -> std::result::Result<Response<PauseJobResponse>, Status> {
+        self.coordinator.pause_job(&request.into_inner().job_id);
+        Ok(Response::new(PauseJobResponse {}))
+    }
+
+    async fn resume_job(&self, request: Request<ResumeJobRequest>) -> std::result::Result<Response<ResumeJobResponse>, Status> {
+        self.coordinator.resume_job(&request.into_inner().job_id);
+        Ok(Response::new(ResumeJobResponse {}))
+    }
+
+    async fn cancel_job(&self, request: Request<CancelJobRequest>) -> std::result::Result<Response<CancelJobResponse>, Status> {
+        self.coordinator.cancel_job(&request.into_inner().job_id);
+        Ok(Response::new(CancelJobResponse {}))
+    }
+
+    async fn set_tranquility(&self, request: Request<SetTranquilityRequest>) -> std::result::Result<Response<SetTranquilityResponse>, Status> {
+        let req = request.into_inner();
+        self.coordinator.set_tranquility(&req.job_id, req.tranquility);
+        Ok(Response::new(SetTranquilityResponse {}))
+    }
+
+    /// Registers a recurring job on the coordinator's scheduler, the only
+    /// way to reach `Coordinator::register_schedule` from outside this
+    /// process (the scheduler itself only fires entries already registered).
+    async fn schedule_job(&self, request: Request<ScheduleJobRequest>) -> std::result::Result<Response<ScheduleJobResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.files.is_empty() {
+            return Err(Status::invalid_argument("at least one file is required"));
+        }
+        if self.registry.get_map_function(&req.map_function).is_none() {
+            return Err(Status::not_found(format!("unknown map function '{}'", req.map_function)));
+        }
+        if self.registry.get_reduce_function(&req.reduce_function).is_none() {
+            return Err(Status::not_found(format!("unknown reduce function '{}'", req.reduce_function)));
+        }
+
+        let overlap_policy = match req.overlap_policy.as_str() {
+            "queue" => OverlapPolicy::Queue,
+            _ => OverlapPolicy::Skip,
+        };
+
+        let schedule_id = self.coordinator.register_schedule(ScheduleSpec {
+            server_addr: req.server_addr,
+            files: req.files,
+            map_function: req.map_function,
+            reduce_function: req.reduce_function,
+            interval: std::time::Duration::from_secs(req.interval_secs.max(1)),
+            max_retries: req.max_retries,
+            overlap_policy,
+        });
+
+        Ok(Response::new(ScheduleJobResponse { schedule_id }))
+    }
 }
 
 pub async fn run_server(addr: &str) -> Result<()> {
+    run_server_with_service(addr, MapReduceService::new()).await
+}
+
+/// Like [`run_server`], but serves a caller-built `mapreduce_service` instead
+/// of always constructing one with the built-in registry — the hook a caller
+/// needs to run a server whose `FunctionRegistry` has custom functions
+/// registered (see `examples/custom_functions.rs`).
+pub async fn run_server_with_service(addr: &str, mapreduce_service: MapReduceService) -> Result<()> {
     let addr = addr.parse()?;
-    let mapreduce_service = MapReduceService::new();
 
     println!("Server listening on {}", addr);
 
-    // Start the health check task
-    let coordinator = Arc::clone(&mapreduce_service.coordinator);
-    let coordinator_clone = coordinator.clone();
+    // Drive the worker health check through the generic BackgroundWorker
+    // abstraction rather than a bespoke loop, so its lifecycle (last error,
+    // completed iterations) is introspectable the same way as any other
+    // background task in this crate.
+    background_worker::spawn_background_worker(HealthChecker {
+        coordinator: Arc::clone(&mapreduce_service.coordinator),
+        metrics: Arc::clone(&mapreduce_service.metrics),
+        fluentd: mapreduce_service.fluentd.clone(),
+        self_worker_id: mapreduce_service.worker_id.clone(),
+    });
+
+    // Drive any recurring jobs registered on the coordinator's scheduler.
+    mapreduce_service.coordinator.spawn_scheduler();
+
+    // Keep the coordinator's task map from growing without bound.
+    background_worker::spawn_background_worker(TaskReaper {
+        coordinator: Arc::clone(&mapreduce_service.coordinator),
+    });
+
+    // Expose /metrics on the gRPC port + 1000 so operators have a scrape target.
+    let metrics_addr: std::net::SocketAddr = format!("{}:{}", addr.ip(), addr.port() + 1000).parse()?;
+    let metrics_for_http = Arc::clone(&mapreduce_service.metrics);
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
-        loop {
-            interval.tick().await;
-            coordinator_clone.check_worker_health();
-        }
+        crate::metrics::serve(metrics_for_http, metrics_addr).await;
     });
 
+    // When configured, also pull-consume tasks off the JetStream work queue
+    // instead of relying solely on direct gRPC calls.
+    if mapreduce_service.config.transport == crate::config::TransportBackend::Jetstream {
+        let nats_url = mapreduce_service.config.nats_url.clone();
+        let registry = Arc::clone(&mapreduce_service.registry);
+        let s3_config = mapreduce_service.config.s3.clone();
+        let jetstream_coordinator = Arc::clone(&mapreduce_service.coordinator);
+        tokio::spawn(async move {
+            if let Err(e) = run_jetstream_worker(&nats_url, registry, s3_config, jetstream_coordinator).await {
+                eprintln!("jetstream worker exited: {}", e);
+            }
+        });
+    }
+
     Server::builder()
         .add_service(MapReduceServiceServer::new(mapreduce_service))
         .serve(addr)
@@ -145,6 +471,243 @@ pub async fn run_server(addr: &str) -> Result<()> {
     Ok(())
 }
 
+/// How often [`HealthChecker`] sweeps for stale workers.
+const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Sweeps for workers whose heartbeat has gone stale, reports newly-dead ones
+/// to Fluentd, and keeps the worker gauges in sync. Also re-heartbeats this
+/// server's own self-registered worker entry directly, since its `map`/
+/// `reduce` handlers run in-process rather than pinging in over the `ping`
+/// RPC the way a real remote worker (registered via `register_worker` and
+/// kept alive by `client::run_worker`) does.
+struct HealthChecker {
+    coordinator: Arc<Coordinator>,
+    metrics: Arc<Metrics>,
+    fluentd: FluentdSink,
+    self_worker_id: String,
+}
+
+#[async_trait::async_trait]
+impl BackgroundWorker for HealthChecker {
+    async fn work(&mut self) -> std::result::Result<WorkerState, Box<dyn std::error::Error + Send + Sync>> {
+        self.coordinator.heartbeat(self.self_worker_id.clone());
+
+        for worker_id in self.coordinator.check_worker_health() {
+            self.fluentd.emit("worker.unhealthy", fluentd::fields(&[("worker_id", &worker_id)])).await;
+        }
+        self.metrics.set_worker_counts(
+            self.coordinator.worker_count() as i64,
+            self.coordinator.healthy_worker_count() as i64,
+        );
+
+        Ok(WorkerState::Idle { wait_until: std::time::SystemTime::now() + HEALTH_CHECK_INTERVAL })
+    }
+}
+
+/// How often [`TaskReaper`] sweeps for finished tasks past `TASK_RETENTION`.
+const TASK_REAP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Periodically evicts terminal tasks from the coordinator's in-memory map,
+/// so a long-running server doesn't accumulate one entry per `map`/`reduce`
+/// call for the lifetime of the process.
+struct TaskReaper {
+    coordinator: Arc<Coordinator>,
+}
+
+#[async_trait::async_trait]
+impl BackgroundWorker for TaskReaper {
+    async fn work(&mut self) -> std::result::Result<WorkerState, Box<dyn std::error::Error + Send + Sync>> {
+        self.coordinator.evict_finished_tasks();
+        Ok(WorkerState::Idle { wait_until: std::time::SystemTime::now() + TASK_REAP_INTERVAL })
+    }
+}
+
+/// Pull-consumes map and reduce tasks off the JetStream work queue and
+/// executes them through the same `FunctionRegistry` and input sources the
+/// gRPC path uses, so `map`/`reduce` behave identically regardless of which
+/// transport dispatched them.
+async fn run_jetstream_worker(
+    nats_url: &str,
+    registry: Arc<FunctionRegistry>,
+    s3_config: S3Config,
+    coordinator: Arc<Coordinator>,
+) -> Result<()> {
+    let transport = Arc::new(JetStreamTransport::connect(nats_url).await?);
+
+    let map_transport = Arc::clone(&transport);
+    let map_registry = Arc::clone(&registry);
+    let map_s3 = s3_config.clone();
+    let map_coordinator = Arc::clone(&coordinator);
+    let map_tranquilizer = Arc::new(tokio::sync::Mutex::new(Tranquilizer::new()));
+    let map_loop = tokio::spawn(async move {
+        loop {
+            let registry = Arc::clone(&map_registry);
+            let s3 = map_s3.clone();
+            let transport = Arc::clone(&map_transport);
+            let coordinator = Arc::clone(&map_coordinator);
+            let tranquilizer = Arc::clone(&map_tranquilizer);
+            let result = map_transport
+                .consume_map_tasks(8, move |task: MapTask| {
+                    let registry = Arc::clone(&registry);
+                    let s3 = s3.clone();
+                    let transport = Arc::clone(&transport);
+                    let coordinator = Arc::clone(&coordinator);
+                    let tranquilizer = Arc::clone(&tranquilizer);
+                    async move {
+                        if await_job_control(&coordinator, &task.job_id).await {
+                            return Ok(());
+                        }
+                        let mut tranquilizer = tranquilizer.lock().await;
+                        let in_flight = tranquilizer.begin();
+                        let pairs = execute_map_task(&task, &registry, &s3).await?;
+
+                        // Hash-partition this task's output the same way the
+                        // gRPC client does for its own reduce fan-out, then
+                        // stage each non-empty partition under a key unique
+                        // to this task so concurrent map tasks for the same
+                        // job never clobber each other's output.
+                        let partitions = crate::shuffle::partition_pairs(pairs, task.num_reducers.max(1));
+                        for (partition, pairs) in partitions.into_iter().enumerate() {
+                            if pairs.is_empty() {
+                                continue;
+                            }
+                            transport.stage_partition(&task.job_id, partition, &task.task_id, &pairs).await?;
+                        }
+                        transport.publish_map_done(&task.job_id, &task.task_id).await?;
+
+                        tranquilizer.tranquilize(in_flight, coordinator.watch_job(&task.job_id).borrow().tranquility).await;
+                        Ok(())
+                    }
+                })
+                .await;
+            if let Err(e) = result {
+                eprintln!("map consumer error: {}", e);
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    });
+
+    let reduce_transport = Arc::clone(&transport);
+    let reduce_registry = Arc::clone(&registry);
+    let reduce_coordinator = Arc::clone(&coordinator);
+    let reduce_tranquilizer = Arc::new(tokio::sync::Mutex::new(Tranquilizer::new()));
+    let reduce_loop = tokio::spawn(async move {
+        loop {
+            let registry = Arc::clone(&reduce_registry);
+            let transport = Arc::clone(&reduce_transport);
+            let coordinator = Arc::clone(&reduce_coordinator);
+            let tranquilizer = Arc::clone(&reduce_tranquilizer);
+            let result = reduce_transport
+                .consume_reduce_tasks(8, move |task: ReduceTask| {
+                    let registry = Arc::clone(&registry);
+                    let transport = Arc::clone(&transport);
+                    let coordinator = Arc::clone(&coordinator);
+                    let tranquilizer = Arc::clone(&tranquilizer);
+                    async move {
+                        if await_job_control(&coordinator, &task.job_id).await {
+                            return Ok(());
+                        }
+                        let mut tranquilizer = tranquilizer.lock().await;
+                        let in_flight = tranquilizer.begin();
+                        let job_id = task.job_id.clone();
+                        let partition = task.partition;
+                        let result = execute_reduce_task(&task, &registry, &transport).await?;
+                        transport.publish_reduce_done(&job_id, partition, &result).await?;
+                        tranquilizer.tranquilize(in_flight, coordinator.watch_job(&task.job_id).borrow().tranquility).await;
+                        Ok(())
+                    }
+                })
+                .await;
+            if let Err(e) = result {
+                eprintln!("reduce consumer error: {}", e);
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    });
+
+    let _ = tokio::join!(map_loop, reduce_loop);
+    Ok(())
+}
+
+/// Checked between task units: blocks while `job_id` is paused, and reports
+/// whether the task should be skipped because the job was cancelled.
+async fn await_job_control(coordinator: &Coordinator, job_id: &str) -> bool {
+    let mut control = coordinator.watch_job(job_id);
+    loop {
+        match control.borrow().command {
+            JobControl::Cancel => return true,
+            JobControl::Resume => return false,
+            JobControl::Pause => {}
+        }
+        if control.changed().await.is_err() {
+            return false;
+        }
+    }
+}
+
+async fn execute_map_task(task: &MapTask, registry: &Arc<FunctionRegistry>, s3_config: &S3Config) -> Result<Vec<KeyValuePair>> {
+    if registry.get_map_function(&task.map_function).is_none() {
+        return Err(format!("unknown map function '{}'", task.map_function).into());
+    }
+
+    let source = input_source::resolve(&task.file_path, s3_config);
+    let length = if task.length > 0 { Some(task.length) } else { None };
+    let mut reader = source.open_range(&task.file_path, task.offset, length).await?;
+
+    let mut buffer = String::new();
+    let mut lines = Vec::new();
+    while let Ok(n) = reader.read_line(&mut buffer).await {
+        if n == 0 { break; }
+        lines.push(std::mem::take(&mut buffer));
+    }
+
+    // CPU-bound, so it runs on the blocking pool rather than the async
+    // runtime's own worker threads, consistent with the gRPC `map` handler.
+    let registry = Arc::clone(registry);
+    let map_function = task.map_function.clone();
+    let pairs = tokio::task::spawn_blocking(move || {
+        let map_fn = registry.get_map_function(&map_function).expect("checked before spawning");
+        lines
+            .iter()
+            .flat_map(|line| map_fn.map(line))
+            .map(|(key, value)| KeyValuePair { key, value })
+            .collect::<Vec<_>>()
+    })
+    .await?;
+
+    Ok(pairs)
+}
+
+async fn execute_reduce_task(task: &ReduceTask, registry: &Arc<FunctionRegistry>, transport: &JetStreamTransport) -> Result<String> {
+    if registry.get_reduce_function(&task.reduce_function).is_none() {
+        return Err(format!("unknown reduce function '{}'", task.reduce_function).into());
+    }
+
+    let pairs = transport.load_existing_partitions(&task.object_keys).await?;
+
+    // CPU-bound, so it runs on the blocking pool rather than the async
+    // runtime's own worker threads, consistent with the gRPC `reduce` handler.
+    let registry = Arc::clone(registry);
+    let reduce_function = task.reduce_function.clone();
+    let final_result = tokio::task::spawn_blocking(move || {
+        let reduce_fn = registry.get_reduce_function(&reduce_function).expect("checked before spawning");
+
+        let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+        for kv in pairs {
+            grouped.entry(kv.key).or_default().push(kv.value);
+        }
+
+        grouped
+            .into_iter()
+            .map(|(key, values)| format!("{}:{}", key, reduce_fn.reduce(&key, values)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    })
+    .await?;
+
+    Ok(final_result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +734,9 @@ mod tests {
         let request = Request::new(MapRequest {
             file_path: file_path.to_str().unwrap().to_string(),
             map_function: DEFAULT_MAP_FN.to_string(),
+            offset: 0,
+            length: 0,
+            job_id: "test-job".to_string(),
         });
 
         // Call map
@@ -195,6 +761,9 @@ mod tests {
         let request = Request::new(MapRequest {
             file_path: "/nonexistent/file.txt".to_string(),
             map_function: DEFAULT_MAP_FN.to_string(),
+            offset: 0,
+            length: 0,
+            job_id: "test-job".to_string(),
         });
 
         let result = service.map(request).await;
@@ -217,6 +786,8 @@ mod tests {
                 },
             ],
             reduce_function: DEFAULT_REDUCE_FN.to_string(),
+            num_reducers: 1,
+            job_id: "test-job".to_string(),
         });
 
         let response = service.reduce(request).await.unwrap();
@@ -227,21 +798,44 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_reduce_with_invalid_value() {
+    async fn test_reduce_with_unknown_function() {
         let service = MapReduceService::new();
         let request = Request::new(ReduceRequest {
             intermediate_results: vec![
                 KeyValuePair {
                     key: "hello".to_string(),
-                    value: "not_a_number".to_string(),
+                    value: "1".to_string(),
                 },
             ],
-            reduce_function: DEFAULT_REDUCE_FN.to_string(),
+            reduce_function: "does_not_exist".to_string(),
+            num_reducers: 1,
+            job_id: "test-job".to_string(),
         });
 
         let result = service.reduce(request).await;
         assert!(result.is_err());
-        assert_matches!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+        assert_matches!(result.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_map_with_unknown_function() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "hello world").unwrap();
+
+        let service = MapReduceService::new();
+        let request = Request::new(MapRequest {
+            file_path: file_path.to_str().unwrap().to_string(),
+            map_function: "does_not_exist".to_string(),
+            offset: 0,
+            length: 0,
+            job_id: "test-job".to_string(),
+        });
+
+        let result = service.map(request).await;
+        assert!(result.is_err());
+        assert_matches!(result.unwrap_err().code(), tonic::Code::NotFound);
     }
 
     #[tokio::test]
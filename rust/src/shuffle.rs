@@ -0,0 +1,83 @@
+//! Hash-partitioned shuffle: splits intermediate key-value pairs into a fixed
+//! number of partitions so every occurrence of a key lands with the same
+//! reducer, regardless of which map task produced it.
+use crate::mapreduce::KeyValuePair;
+
+/// FNV-1a, chosen over `std::hash::DefaultHasher` because its output must be
+/// stable across processes and Rust versions for the same key to always land
+/// in the same partition.
+pub fn stable_hash(key: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in key.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Which partition (0..num_partitions) a key belongs to.
+pub fn partition_of(key: &str, num_partitions: usize) -> usize {
+    (stable_hash(key) % num_partitions as u64) as usize
+}
+
+/// Splits `pairs` into `num_partitions` groups by `partition_of(key)`, so a
+/// single key's values are never split across reducers.
+pub fn partition_pairs(pairs: Vec<KeyValuePair>, num_partitions: usize) -> Vec<Vec<KeyValuePair>> {
+    let num_partitions = num_partitions.max(1);
+    let mut partitions: Vec<Vec<KeyValuePair>> = (0..num_partitions).map(|_| Vec::new()).collect();
+    for pair in pairs {
+        let idx = partition_of(&pair.key, num_partitions);
+        partitions[idx].push(pair);
+    }
+    partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash_is_deterministic_across_calls() {
+        assert_eq!(stable_hash("hello"), stable_hash("hello"));
+        assert_ne!(stable_hash("hello"), stable_hash("world"));
+    }
+
+    #[test]
+    fn partition_of_is_stable_for_the_same_key() {
+        for key in ["hello", "world", "", "a-much-longer-key-than-the-others"] {
+            let first = partition_of(key, 8);
+            for _ in 0..100 {
+                assert_eq!(partition_of(key, 8), first);
+            }
+        }
+    }
+
+    #[test]
+    fn partition_of_spreads_keys_across_partitions() {
+        let num_partitions = 8;
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..1000 {
+            seen.insert(partition_of(&format!("key-{i}"), num_partitions));
+        }
+        // Not a strict uniformity check, just that keys don't all collapse
+        // into a single partition.
+        assert!(seen.len() > 1, "expected keys to spread across partitions, got {seen:?}");
+    }
+
+    #[test]
+    fn partition_pairs_keeps_same_key_together() {
+        let pairs = vec![
+            KeyValuePair { key: "a".to_string(), value: "1".to_string() },
+            KeyValuePair { key: "a".to_string(), value: "2".to_string() },
+            KeyValuePair { key: "b".to_string(), value: "3".to_string() },
+        ];
+        let partitions = partition_pairs(pairs, 4);
+
+        let a_partition = partition_of("a", 4);
+        assert_eq!(partitions[a_partition].len(), 2);
+        assert!(partitions[a_partition].iter().all(|kv| kv.key == "a"));
+    }
+}
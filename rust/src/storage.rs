@@ -0,0 +1,146 @@
+//! Pluggable persistence for coordinator task state, so job progress
+//! survives a server restart. Mirrors Garage's db-adapter pattern: a single
+//! trait with interchangeable embedded-database backends, selected by
+//! config rather than compiled in.
+use std::sync::{Arc, Mutex};
+
+use crate::config::{Config, PersistenceBackend};
+use crate::coordinator::Task;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Result<T> = std::result::Result<T, Error>;
+
+/// Persists and reloads `Task` state so a coordinator restart can recover
+/// in-flight job progress instead of starting from nothing.
+pub trait TaskStore: Send + Sync {
+    /// Upserts `task`'s full state. Called on every `assign_task` /
+    /// `update_task_status` transition so the store never lags the
+    /// in-memory map by more than one transition.
+    fn save_task(&self, task: &Task) -> Result<()>;
+
+    /// Loads every persisted task, used once at startup to seed the
+    /// in-memory map.
+    fn load_all_tasks(&self) -> Result<Vec<Task>>;
+}
+
+/// Discards everything; used when no persistence path is configured so
+/// `Coordinator` doesn't need a separate in-memory-only code path.
+pub struct NullTaskStore;
+
+impl TaskStore for NullTaskStore {
+    fn save_task(&self, _task: &Task) -> Result<()> {
+        Ok(())
+    }
+
+    fn load_all_tasks(&self) -> Result<Vec<Task>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Embedded key-value backend: one entry per task, keyed by task id.
+pub struct SledTaskStore {
+    db: sled::Db,
+}
+
+impl SledTaskStore {
+    pub fn open(path: &str) -> Result<Self> {
+        Ok(Self { db: sled::open(path)? })
+    }
+}
+
+impl TaskStore for SledTaskStore {
+    fn save_task(&self, task: &Task) -> Result<()> {
+        let value = serde_json::to_vec(task)?;
+        self.db.insert(task.id.as_bytes(), value)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn load_all_tasks(&self) -> Result<Vec<Task>> {
+        self.db
+            .iter()
+            .values()
+            .map(|v| Ok(serde_json::from_slice(&v?)?))
+            .collect()
+    }
+}
+
+/// Embedded relational backend: one row per task.
+pub struct SqliteTaskStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+impl SqliteTaskStore {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = rusqlite::Connection::open(path)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id TEXT PRIMARY KEY,
+                job_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                worker_id TEXT,
+                retries INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl TaskStore for SqliteTaskStore {
+    fn save_task(&self, task: &Task) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO tasks (id, job_id, status, worker_id, retries) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET
+                job_id = excluded.job_id,
+                status = excluded.status,
+                worker_id = excluded.worker_id,
+                retries = excluded.retries",
+            rusqlite::params![
+                task.id,
+                task.job_id,
+                serde_json::to_string(&task.status)?,
+                task.worker_id,
+                task.retries,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn load_all_tasks(&self) -> Result<Vec<Task>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, job_id, status, worker_id, retries FROM tasks")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, u32>(4)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()?;
+
+        rows.into_iter()
+            .map(|(id, job_id, status_json, worker_id, retries)| {
+                Ok(Task { id, job_id, status: serde_json::from_str(&status_json)?, worker_id, retries })
+            })
+            .collect()
+    }
+}
+
+/// Builds the backend selected by `config`, or [`NullTaskStore`] when no
+/// persistence path is set so the coordinator stays purely in-memory by
+/// default.
+pub fn from_config(config: &Config) -> Result<Arc<dyn TaskStore>> {
+    let Some(path) = &config.persistence_path else {
+        return Ok(Arc::new(NullTaskStore));
+    };
+
+    match config.persistence_backend {
+        PersistenceBackend::Sled => Ok(Arc::new(SledTaskStore::open(path)?)),
+        PersistenceBackend::Sqlite => Ok(Arc::new(SqliteTaskStore::open(path)?)),
+    }
+}
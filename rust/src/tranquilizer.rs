@@ -0,0 +1,122 @@
+//! Adaptive throttling for CPU-heavy map/reduce work: a worker calls
+//! [`Tranquilizer::begin`] before a unit of work and passes the
+//! [`InFlight`] it returns to [`Tranquilizer::tranquilize`] after, which
+//! sleeps for roughly `tranquility * d` (`d` the unit's busy duration) so
+//! the worker spends about `1 / (tranquility + 1)` of wall-clock time busy
+//! instead of saturating the machine. `tranquility` of `0` (the default)
+//! disables throttling entirely.
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Never sleep longer than this between units of work, regardless of how
+/// high `tranquility` or recent durations are.
+const MAX_SLEEP: Duration = Duration::from_secs(2);
+
+/// How many recent unit durations to average over, to smooth out one-off
+/// spikes instead of reacting to them directly.
+const MOVING_AVERAGE_WINDOW: usize = 5;
+
+pub struct Tranquilizer {
+    recent_durations: VecDeque<Duration>,
+}
+
+/// A unit of work in progress, returned by [`Tranquilizer::begin`] and
+/// consumed by [`Tranquilizer::tranquilize`]. Carrying the start time here
+/// rather than on `Tranquilizer` itself lets one `Tranquilizer` be shared
+/// (e.g. behind a `Mutex`) across concurrent units of work without one
+/// call's `begin()` clobbering another's in-flight start time.
+pub struct InFlight(Instant);
+
+impl Tranquilizer {
+    pub fn new() -> Self {
+        Self { recent_durations: VecDeque::with_capacity(MOVING_AVERAGE_WINDOW) }
+    }
+
+    /// Records the start of a unit of work.
+    pub fn begin(&self) -> InFlight {
+        InFlight(Instant::now())
+    }
+
+    /// Ends the unit of work started by `in_flight` and sleeps for
+    /// `tranquility` times the moving average of recent unit durations,
+    /// clamped to `MAX_SLEEP`. A `tranquility` of `0` never sleeps.
+    pub async fn tranquilize(&mut self, in_flight: InFlight, tranquility: u32) {
+        let elapsed = in_flight.0.elapsed();
+
+        if self.recent_durations.len() == MOVING_AVERAGE_WINDOW {
+            self.recent_durations.pop_front();
+        }
+        self.recent_durations.push_back(elapsed);
+
+        if tranquility == 0 {
+            return;
+        }
+
+        let average = self.recent_durations.iter().sum::<Duration>() / self.recent_durations.len() as u32;
+        let sleep_for = average.saturating_mul(tranquility).min(MAX_SLEEP);
+        if !sleep_for.is_zero() {
+            tokio::time::sleep(sleep_for).await;
+        }
+    }
+}
+
+impl Default for Tranquilizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn tranquility_of_zero_never_sleeps() {
+        let mut tranquilizer = Tranquilizer::new();
+        let in_flight = tranquilizer.begin();
+        let before = tokio::time::Instant::now();
+        tranquilizer.tranquilize(in_flight, 0).await;
+        assert_eq!(tokio::time::Instant::now(), before);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sleep_is_clamped_to_max_sleep() {
+        let mut tranquilizer = Tranquilizer::new();
+        // Fabricate a long recent duration so a huge tranquility would
+        // otherwise ask for a sleep far beyond `MAX_SLEEP`.
+        tranquilizer.recent_durations.push_back(Duration::from_secs(10));
+        let in_flight = tranquilizer.begin();
+        let before = tokio::time::Instant::now();
+        tranquilizer.tranquilize(in_flight, 1000).await;
+        assert!(tokio::time::Instant::now() - before <= MAX_SLEEP);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_callers_each_measure_their_own_unit() {
+        // Two in-flight units started at different times must each report
+        // their own elapsed duration, not whichever `begin()` ran last —
+        // the bug a shared `started_at` field would reintroduce.
+        let mut tranquilizer = Tranquilizer::new();
+        let first = tranquilizer.begin();
+        tokio::time::advance(Duration::from_millis(500)).await;
+        let second = tranquilizer.begin();
+        tokio::time::advance(Duration::from_millis(500)).await;
+
+        tranquilizer.tranquilize(second, 0).await;
+        assert_eq!(tranquilizer.recent_durations.back(), Some(&Duration::from_millis(500)));
+
+        tranquilizer.tranquilize(first, 0).await;
+        assert_eq!(tranquilizer.recent_durations.back(), Some(&Duration::from_millis(1000)));
+    }
+
+    #[tokio::test]
+    async fn moving_average_window_does_not_grow_unbounded() {
+        let mut tranquilizer = Tranquilizer::new();
+        for _ in 0..(MOVING_AVERAGE_WINDOW * 3) {
+            let in_flight = tranquilizer.begin();
+            tranquilizer.tranquilize(in_flight, 0).await;
+        }
+        assert_eq!(tranquilizer.recent_durations.len(), MOVING_AVERAGE_WINDOW);
+    }
+}
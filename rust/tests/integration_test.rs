@@ -67,18 +67,25 @@ async fn test_worker_health_tracking() {
     tokio::time::sleep(Duration::from_millis(100)).await;
 
     // Create a client and register as a worker
-    let worker_id = "test-worker-1";
     let mut client = MapReduceServiceClient::connect(
         format!("http://{}", server_addr)
     ).await.unwrap();
 
+    let register_response = client
+        .register_worker(tonic::Request::new(distributed_mapreduce::mapreduce::RegisterWorkerRequest {
+            address: "http://[::1]:60000".to_string(),
+        }))
+        .await
+        .unwrap();
+    let worker_id = register_response.into_inner().worker_id;
+
     // Send initial ping
     let mut request = tonic::Request::new(distributed_mapreduce::mapreduce::PingRequest {});
     request.metadata_mut().insert(
         "worker-id",
         worker_id.parse().unwrap()
     );
-    
+
     let response = client.ping(request).await.unwrap();
     assert_eq!(response.into_inner().status, "OK");
 